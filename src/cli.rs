@@ -1,5 +1,10 @@
-use clap::{Parser, Subcommand};
+use crate::completions::{complete_course, complete_exercise, complete_exercise_type};
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
+use clap_complete::engine::ArgValueCompleter;
+use std::path::PathBuf;
+use study::config::{ExerciseStatus, parse_due};
 
 #[derive(Parser)]
 #[command(name = "study", about = "Course management CLI")]
@@ -8,10 +13,24 @@ pub struct Args {
     pub command: Option<Command>,
 
     /// Course name (shorthand for `study <course>`)
+    #[arg(add = ArgValueCompleter::new(complete_course))]
     pub course: Option<String>,
 
     /// Exercise name (defaults to last accessed)
+    #[arg(add = ArgValueCompleter::new(complete_exercise))]
     pub exercise: Option<String>,
+
+    /// Wait for background study commands to exit
+    #[arg(short, long)]
+    pub wait: bool,
+
+    /// Include archived courses and courses from other terms
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Treat unknown keys in config.toml and course.toml as errors
+    #[arg(long, global = true)]
+    pub strict: bool,
 }
 
 #[derive(Subcommand)]
@@ -24,9 +43,168 @@ pub enum Command {
     /// Start a study session for a course
     Study {
         /// Course name
+        #[arg(add = ArgValueCompleter::new(complete_course))]
         course: String,
         /// Exercise name (defaults to last accessed)
+        #[arg(add = ArgValueCompleter::new(complete_exercise))]
+        exercise: Option<String>,
+        /// Wait for background study commands to exit
+        #[arg(short, long)]
+        wait: bool,
+        /// Include archived courses and courses from other terms
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Stop the background commands started by the last study session
+    Stop,
+    /// Show or update an exercise's status, deadline, points, grade and tags
+    Status {
+        /// Exercise name, number or title
+        #[arg(add = ArgValueCompleter::new(complete_exercise))]
+        exercise: String,
+        /// New status
+        #[arg(value_enum)]
+        status: Option<ExerciseStatus>,
+        /// Course name (instead of detecting from current directory)
+        #[arg(short, long, add = ArgValueCompleter::new(complete_course))]
+        course: Option<String>,
+        /// Deadline (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_due)]
+        due: Option<NaiveDate>,
+        /// Points received
+        #[arg(long)]
+        points: Option<f64>,
+        /// Grade received
+        #[arg(long)]
+        grade: Option<String>,
+        /// Add a tag
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Remove a tag
+        #[arg(long = "untag", value_name = "TAG")]
+        untags: Vec<String>,
+    },
+    /// Pack an exercise into a submission archive and mark it submitted
+    Pack {
+        /// Exercise name, number or title (defaults to the current or last exercise)
+        #[arg(add = ArgValueCompleter::new(complete_exercise))]
         exercise: Option<String>,
+        /// Course name (instead of detecting from current directory)
+        #[arg(short, long, add = ArgValueCompleter::new(complete_course))]
+        course: Option<String>,
+        /// Archive path (overrides the configured `output`)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Skip the configured build commands
+        #[arg(long)]
+        no_build: bool,
+        /// List the files that would be packed without writing anything
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+    /// List upcoming deadlines across all courses
+    Due {
+        /// Include finished exercises, archived courses and other terms
+        #[arg(short, long)]
+        all: bool,
+        /// Output JSON (shorthand for `--format json`)
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Show past study sessions, most recent first
+    Log {
+        /// Only sessions of this course
+        #[arg(add = ArgValueCompleter::new(complete_course))]
+        course: Option<String>,
+        /// Show at most this many sessions
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+        /// Output JSON (shorthand for `--format json`)
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Show time studied per course, exercise and week
+    Stats {
+        /// Only sessions of this course
+        #[arg(add = ArgValueCompleter::new(complete_course))]
+        course: Option<String>,
+        /// Output JSON (shorthand for `--format json`)
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Record the end of a session once its commands exit (used internally)
+    #[command(hide = true)]
+    WatchSession { id: i64 },
+    /// List courses, or the exercises in a course
+    List {
+        /// Course name (lists its exercises instead of all courses)
+        #[arg(add = ArgValueCompleter::new(complete_course))]
+        course: Option<String>,
+        /// Include archived courses and courses from other terms
+        #[arg(short, long)]
+        all: bool,
+        /// Output JSON (shorthand for `--format json`)
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Archive a course, or every course of a term
+    Archive {
+        /// Course name
+        #[arg(
+            required_unless_present = "term",
+            add = ArgValueCompleter::new(complete_course)
+        )]
+        course: Option<String>,
+        /// Archive all courses whose `term` matches
+        #[arg(long, conflicts_with = "course")]
+        term: Option<String>,
+    },
+    /// Show how a course code is split into faculty and number
+    ParseCode {
+        /// Course code to parse
+        input: String,
+        /// Treat the code as unparsed, as `init course --raw` does
+        #[arg(short, long)]
+        raw: bool,
+    },
+    /// Import deadlines from another format
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Export deadlines to another format
+    Export {
+        #[command(subcommand)]
+        target: ExportTarget,
+    },
+    /// Inspect exercise templates
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+    /// Check the global config, every course and the saved state for problems
+    Doctor {
+        /// Rewrite config files written by an older study in the current format
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Print a JSON Schema for a config file, for editor completion and validation
+    Schema {
+        /// Which file the schema describes
+        #[arg(value_enum)]
+        file: SchemaFile,
     },
     /// Generate shell completions
     Completions {
@@ -44,17 +222,98 @@ pub enum InitTarget {
         /// Skip faculty parsing; place under undefined/<code>
         #[arg(short, long)]
         raw: bool,
+        /// Human-readable course name
+        #[arg(long)]
+        title: Option<String>,
+        /// Start from a preset in ~/.config/study/presets (or a built-in one)
+        #[arg(short, long)]
+        preset: Option<String>,
+        /// Add an exercise type as name=template[:scheme] (repeatable)
+        #[arg(short = 't', long = "type", value_name = "SPEC")]
+        types: Vec<String>,
     },
     /// Initialize a new exercise in the current course
     Exercise {
         /// Exercise type (from course config)
-        #[arg(short, long)]
+        #[arg(short, long, add = ArgValueCompleter::new(complete_exercise_type))]
         r#type: Option<String>,
         /// Custom exercise directory name (overrides auto-naming)
         #[arg(short, long)]
         name: Option<String>,
         /// Course name (instead of detecting from current directory)
+        #[arg(short, long, add = ArgValueCompleter::new(complete_course))]
+        course: Option<String>,
+        /// Deadline (YYYY-MM-DD)
+        #[arg(short, long, value_parser = parse_due)]
+        due: Option<NaiveDate>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ImportSource {
+    /// Set exercise deadlines from the events and to-dos in an iCalendar file
+    Ics {
+        /// iCalendar (.ics) file
+        file: PathBuf,
+        /// Course the deadlines belong to
+        #[arg(short, long, add = ArgValueCompleter::new(complete_course))]
+        course: String,
+        /// Exercise type to match numbers against and to create exercises with
         #[arg(short, long)]
+        r#type: Option<String>,
+        /// Create exercises for entries that match none
+        #[arg(long)]
+        create: bool,
+        /// Show what would change without writing anything
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportTarget {
+    /// Write all deadlines as an iCalendar file
+    Ics {
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Include archived courses and courses from other terms
+        #[arg(short, long)]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TemplateAction {
+    /// List templates available to a course
+    List {
+        /// Course name (instead of detecting from current directory)
+        #[arg(short, long, add = ArgValueCompleter::new(complete_course))]
         course: Option<String>,
     },
+    /// Show where a template comes from, what it extends and its files
+    Show {
+        /// Template name
+        name: String,
+        /// Course name (instead of detecting from current directory)
+        #[arg(short, long, add = ArgValueCompleter::new(complete_course))]
+        course: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaFile {
+    /// A course's course.toml
+    Course,
+    /// The global config.toml
+    Global,
+    /// An exercise's meta.toml
+    Meta,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Toml,
 }
@@ -0,0 +1,44 @@
+use std::io;
+use study::{Course, Workspace};
+
+pub fn run(workspace: &Workspace, course: Option<&str>, term: Option<&str>) -> io::Result<()> {
+    let targets: Vec<Course> = match (course, term) {
+        // Courses of past terms are what gets archived, so look beyond the active one.
+        (Some(name), _) => {
+            let course = workspace.find_course(name, true)?;
+            if workspace.is_archived(&course) {
+                println!("Already archived: {}", course.dir().display());
+                return Ok(());
+            }
+            vec![course]
+        }
+        (None, Some(term)) => workspace
+            .courses(true)
+            .into_iter()
+            .filter(|course| {
+                course.config().course.term.as_deref() == Some(term)
+                    && !workspace.is_archived(course)
+            })
+            .collect(),
+        (None, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Specify a course or --term <term>.",
+            ));
+        }
+    };
+
+    if targets.is_empty() {
+        println!("No courses to archive.");
+        return Ok(());
+    }
+
+    for course in targets {
+        match workspace.archive(&course) {
+            Ok(dest) => println!("Archived {}", dest.display()),
+            Err(e) => eprintln!("Failed to archive {}: {}", course.dir().display(), e),
+        }
+    }
+
+    Ok(())
+}
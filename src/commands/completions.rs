@@ -1,10 +1,24 @@
 use crate::cli::Args;
 use clap::CommandFactory;
-use clap_complete::generate;
+use clap_complete::env::Shells;
 use std::io;
 
+/// Print the shell registration script for dynamic completions.
+///
+/// The script calls back into `study` (via the `COMPLETE` environment
+/// variable) on every `<TAB>`, so course, exercise and type candidates are
+/// read from disk at completion time.
 pub fn run(shell: clap_complete::Shell) -> io::Result<()> {
-    let mut cmd = Args::command();
-    generate(shell, &mut cmd, "study", &mut io::stdout());
-    Ok(())
+    let cmd = Args::command();
+    let name = cmd.get_name();
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Dynamic completions are not supported for {}", shell),
+            )
+        })?;
+    completer.write_registration("COMPLETE", name, name, name, &mut io::stdout())
 }
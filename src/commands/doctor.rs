@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use study::Error;
+use study::config::{
+    CONFIG_VERSION, ConfigFile, CourseConfig, GlobalConfig, course_dirs, exercise_dirs,
+    load_course_config_file, load_global_config_file, load_meta, load_state, state_path,
+};
+use study::naming::NamingScheme;
+use study::schema;
+use study::template::TemplateRegistry;
+
+/// Problems found so far. Each is printed as soon as it is found.
+#[derive(Default)]
+struct Report {
+    strict: bool,
+    fix: bool,
+    errors: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn error(&mut self, message: impl Display, hint: Option<String>) {
+        self.errors += 1;
+        print_problem("error", message, hint);
+    }
+
+    fn warning(&mut self, message: impl Display, hint: Option<String>) {
+        self.warnings += 1;
+        print_problem("warning", message, hint);
+    }
+
+    /// Unknown keys are warnings, or errors if `strict`.
+    fn unknown_keys(&mut self, errors: Vec<Error>) {
+        for error in errors {
+            if self.strict {
+                self.config_error(&error);
+            } else {
+                self.warnings += 1;
+                print!("{}", error.render_warning());
+            }
+        }
+    }
+
+    /// A config file that failed to load, shown with the offending line.
+    fn config_error(&mut self, error: &Error) {
+        self.errors += 1;
+        print!("{}", error.render());
+    }
+
+    /// Unknown keys in a config file that loads. A file written by an older
+    /// study is a warning, or is saved in the current format if `fix`.
+    fn config_file<T>(&mut self, file: &ConfigFile<T>) {
+        self.unknown_keys(file.unknown_key_errors());
+        if !file.is_outdated() {
+            return;
+        }
+        if !self.fix {
+            self.warning(
+                format!(
+                    "{}: config version {} is outdated, the current version is {}",
+                    file.path.display(),
+                    file.version,
+                    CONFIG_VERSION
+                ),
+                Some("run `study doctor --fix` to update it".into()),
+            );
+        } else if let Err(e) = file.save_migrated() {
+            self.config_error(&e);
+        } else {
+            println!(
+                "fixed: {}: updated to config version {}",
+                file.path.display(),
+                CONFIG_VERSION
+            );
+        }
+    }
+}
+
+fn print_problem(severity: &str, message: impl Display, hint: Option<String>) {
+    println!("{}: {}", severity, message);
+    if let Some(hint) = hint {
+        println!("  hint: {}", hint);
+    }
+}
+
+/// Check the global config, every course and the saved state, and report
+/// anything that would make a course or exercise silently disappear. With
+/// `strict`, unknown keys count as errors. With `fix`, outdated config files
+/// are rewritten.
+pub fn run(strict: bool, fix: bool) -> io::Result<()> {
+    let mut report = Report {
+        strict,
+        fix,
+        ..Report::default()
+    };
+
+    let global = check_global(&mut report);
+    let (courses, broken) = check_courses(&global, &mut report);
+    check_duplicates(&courses, &mut report);
+    check_state(&courses, &broken, &mut report);
+
+    let summary = format!(
+        "{} error(s) and {} warning(s) in {} course(s)",
+        report.errors,
+        report.warnings,
+        courses.len()
+    );
+    if report.errors > 0 {
+        return Err(io::Error::other(summary));
+    }
+    if report.warnings > 0 {
+        println!("{}", summary);
+    } else {
+        println!("No problems found in {} course(s).", courses.len());
+    }
+    Ok(())
+}
+
+fn check_global(report: &mut Report) -> GlobalConfig {
+    match load_global_config_file() {
+        Ok(Some(file)) => {
+            report.config_file(&file);
+            file.config
+        }
+        Ok(None) => GlobalConfig::default(),
+        Err(e) => {
+            report.config_error(&e);
+            println!("  note: checking courses with the default config");
+            GlobalConfig::default()
+        }
+    }
+}
+
+/// Check every course, archived ones included. Returns the courses that load,
+/// and the codes of those that don't, as far as they can be read.
+fn check_courses(
+    global: &GlobalConfig,
+    report: &mut Report,
+) -> (Vec<(PathBuf, CourseConfig)>, Vec<String>) {
+    let mut courses = Vec::new();
+    let mut broken = Vec::new();
+    for dir in course_dirs(global, true) {
+        let path = dir.join("course.toml");
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        let schemes_valid = check_naming_schemes(&path, &contents, report);
+
+        let config = match load_course_config_file(&dir) {
+            Ok(file) => {
+                report.config_file(&file);
+                file.config
+            }
+            Err(e) => {
+                // A misspelled key may be why it doesn't load.
+                let keys = schema::unknown_keys(&contents, &schema::COURSE).unwrap_or_default();
+                report.unknown_keys(keys.iter().map(|key| key.error(&path, &contents)).collect());
+                // An invalid naming scheme has been reported with its type already.
+                if schemes_valid {
+                    report.config_error(&e);
+                }
+                broken.extend(raw_course_code(&contents));
+                continue;
+            }
+        };
+
+        check_templates(global, &dir, &config, report);
+        for exercise_dir in exercise_dirs(&dir) {
+            if exercise_dir.join("meta.toml").exists()
+                && let Err(e) = load_meta(&exercise_dir)
+            {
+                report.config_error(&e);
+            }
+        }
+        courses.push((dir, config));
+    }
+    (courses, broken)
+}
+
+/// `course.code` of a `course.toml` that is valid TOML but not a valid config.
+fn raw_course_code(contents: &str) -> Option<String> {
+    let value = toml::from_str::<toml::Value>(contents).ok()?;
+    Some(value.get("course")?.get("code")?.as_str()?.to_string())
+}
+
+/// Report every invalid `naming_scheme`, not just the first one serde stops
+/// at. Returns whether all of them are valid.
+fn check_naming_schemes(path: &Path, contents: &str, report: &mut Report) -> bool {
+    let Ok(value) = toml::from_str::<toml::Value>(contents) else {
+        return true;
+    };
+    let Some(types) = value.get("exercise_types").and_then(|t| t.as_table()) else {
+        return true;
+    };
+    let mut valid = true;
+    for (name, exercise_type) in types {
+        let Some(scheme) = exercise_type.get("naming_scheme").and_then(|s| s.as_str()) else {
+            continue;
+        };
+        if let Err(e) = scheme.parse::<NamingScheme>() {
+            valid = false;
+            report.error(
+                format!("{}: exercise type '{}': {}", path.display(), name, e),
+                Some(
+                    "counters look like {n:02}, {n:a} or {n:A}; see also {week:02} and {date:%m%d}"
+                        .into(),
+                ),
+            );
+        }
+    }
+    valid
+}
+
+fn check_templates(global: &GlobalConfig, dir: &Path, config: &CourseConfig, report: &mut Report) {
+    let registry = TemplateRegistry::new(global, Some((dir, config)));
+    let mut types: Vec<_> = config.exercise_types.iter().collect();
+    types.sort_by_key(|(name, _)| *name);
+    for (name, exercise_type) in types {
+        if let Err(e) = registry.chain(&exercise_type.template) {
+            report.error(
+                format!("{}: exercise type '{}': {}", config.course.code, name, e),
+                e.hint(),
+            );
+        }
+    }
+}
+
+/// Codes, names and aliases shared by several courses, which make looking
+/// those courses up ambiguous.
+fn check_duplicates(courses: &[(PathBuf, CourseConfig)], report: &mut Report) {
+    let mut owners: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, (_, config)) in courses.iter().enumerate() {
+        let course = &config.course;
+        let names = std::iter::once(&course.code)
+            .chain(&course.name)
+            .chain(&course.aliases);
+        for name in names {
+            let owners = owners.entry(name.to_lowercase()).or_default();
+            if !owners.contains(&i) {
+                owners.push(i);
+            }
+        }
+    }
+
+    for (name, owners) in owners.iter().filter(|(_, owners)| owners.len() > 1) {
+        let mut message = format!("'{}' names {} courses:", name, owners.len());
+        for &i in owners {
+            let (dir, config) = &courses[i];
+            message.push_str(&format!("\n  {} ({})", config.course.code, dir.display()));
+        }
+        report.warning(
+            message,
+            Some("rename or archive one of them, or remove the alias".into()),
+        );
+    }
+}
+
+/// Entries in `state.toml` for courses or exercises that no longer exist.
+/// Courses whose config is `broken` have been reported already.
+fn check_state(courses: &[(PathBuf, CourseConfig)], broken: &[String], report: &mut Report) {
+    let state = load_state();
+    let course_dir = |code: &str| {
+        courses
+            .iter()
+            .find(|(_, config)| config.course.code == code)
+            .map(|(dir, _)| dir)
+    };
+    let hint = || Some(format!("remove it from {}", state_path().display()));
+
+    let stale_courses: BTreeSet<&String> = state
+        .last_course
+        .iter()
+        .chain(&state.recent_courses)
+        .filter(|code| !broken.contains(code))
+        .collect();
+    for code in stale_courses {
+        if course_dir(code).is_none() {
+            report.warning(
+                format!("state refers to a missing course: {}", code),
+                hint(),
+            );
+        }
+    }
+
+    let mut last_exercises: Vec<_> = state
+        .last_exercises
+        .iter()
+        .filter(|(code, _)| !broken.contains(code))
+        .collect();
+    last_exercises.sort();
+    for (code, exercise) in last_exercises {
+        match course_dir(code) {
+            None => report.warning(
+                format!("state has a last exercise for a missing course: {}", code),
+                hint(),
+            ),
+            Some(dir) if !dir.join(exercise).is_dir() => report.warning(
+                format!("state refers to a missing exercise: {} {}", code, exercise),
+                hint(),
+            ),
+            Some(_) => {}
+        }
+    }
+}
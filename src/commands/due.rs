@@ -0,0 +1,96 @@
+use super::{print_structured, relative_due};
+use crate::cli::OutputFormat;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::io;
+use std::path::PathBuf;
+use study::{ExerciseStatus, Workspace};
+
+#[derive(Serialize)]
+struct DueList {
+    deadlines: Vec<DueEntry>,
+}
+
+#[derive(Serialize)]
+pub(super) struct DueEntry {
+    pub(super) due: NaiveDate,
+    pub(super) course: String,
+    pub(super) exercise: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) title: Option<String>,
+    pub(super) status: ExerciseStatus,
+    pub(super) path: PathBuf,
+}
+
+pub fn run(workspace: &Workspace, all: bool, format: OutputFormat) -> io::Result<()> {
+    let deadlines = collect(workspace, all, all);
+
+    match format {
+        OutputFormat::Text => print_deadlines(&deadlines),
+        _ => print_structured(&DueList { deadlines }, format)?,
+    }
+    Ok(())
+}
+
+/// Every exercise with a deadline, soonest first. `all` includes archived
+/// courses and other terms; `finished` includes finished exercises.
+pub(super) fn collect(workspace: &Workspace, all: bool, finished: bool) -> Vec<DueEntry> {
+    let mut deadlines = Vec::new();
+    for course in workspace.courses(all) {
+        for exercise in course.exercises() {
+            let Ok(meta) = exercise.meta() else {
+                continue;
+            };
+            let info = meta.exercise;
+            let Some(due) = info.due else {
+                continue;
+            };
+            if info.status.is_finished() && !finished {
+                continue;
+            }
+            deadlines.push(DueEntry {
+                due,
+                course: course.code().to_string(),
+                title: info.title().map(String::from),
+                exercise: info.name,
+                status: info.status,
+                path: exercise.dir().to_path_buf(),
+            });
+        }
+    }
+    deadlines.sort_by(|a, b| (a.due, &a.course, &a.exercise).cmp(&(b.due, &b.course, &b.exercise)));
+    deadlines
+}
+
+fn print_deadlines(deadlines: &[DueEntry]) {
+    if deadlines.is_empty() {
+        println!("No upcoming deadlines.");
+        return;
+    }
+
+    let relative: Vec<String> = deadlines.iter().map(|d| relative_due(d.due)).collect();
+    let relative_width = relative.iter().map(String::len).max().unwrap_or(0);
+    let course_width = deadlines.iter().map(|d| d.course.len()).max().unwrap_or(0);
+    let exercise_width = deadlines
+        .iter()
+        .map(|d| d.exercise.len())
+        .max()
+        .unwrap_or(0);
+    let status_width = deadlines
+        .iter()
+        .map(|d| d.status.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    for (deadline, relative) in deadlines.iter().zip(&relative) {
+        println!(
+            "{}  {:<relative_width$}  {:<course_width$}  {:<exercise_width$}  {:<status_width$}  {}",
+            deadline.due,
+            relative,
+            deadline.course,
+            deadline.exercise,
+            deadline.status.to_string(),
+            deadline.title.as_deref().unwrap_or(""),
+        );
+    }
+}
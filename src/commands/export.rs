@@ -0,0 +1,43 @@
+use super::due;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use study::ics::{self, Deadline};
+use study::{Error, Workspace};
+
+/// Write every known deadline as an iCalendar file, or to stdout.
+pub fn ics(workspace: &Workspace, output: Option<&Path>, all: bool) -> io::Result<()> {
+    let deadlines: Vec<Deadline> = due::collect(workspace, all, true)
+        .into_iter()
+        .map(|entry| {
+            let name = format!("{} {}", entry.course, entry.exercise);
+            Deadline {
+                uid: format!("{}@study", name.replace(char::is_whitespace, "-")),
+                summary: match &entry.title {
+                    Some(title) => format!("{}: {}", name, title),
+                    None => name,
+                },
+                description: Some(format!(
+                    "Status: {}\n{}",
+                    entry.status,
+                    entry.path.display()
+                )),
+                date: entry.due,
+            }
+        })
+        .collect();
+    let calendar = ics::write("study deadlines", &deadlines);
+
+    match output {
+        Some(path) => {
+            fs::write(path, calendar).map_err(|e| Error::io(path, e))?;
+            println!(
+                "Exported {} deadlines to {}",
+                deadlines.len(),
+                path.display()
+            );
+        }
+        None => io::stdout().write_all(calendar.as_bytes())?,
+    }
+    Ok(())
+}
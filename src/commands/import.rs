@@ -0,0 +1,320 @@
+use chrono::NaiveDate;
+use std::io;
+use std::path::{Path, PathBuf};
+use study::config::{edit_meta, exercise_dirs, load_meta};
+use study::ics::{self, Entry};
+use study::{NewExercise, Workspace};
+use toml_edit::value;
+
+/// Key in `meta.toml` remembering which calendar entry an exercise came from.
+const UID_KEY: &str = "calendar_uid";
+
+/// An existing exercise that calendar entries can be matched against.
+struct Exercise {
+    dir: PathBuf,
+    name: String,
+    exercise_type: String,
+    title: Option<String>,
+    number: Option<u32>,
+    uid: Option<String>,
+    due: Option<NaiveDate>,
+}
+
+/// What happened to one calendar entry.
+enum Outcome {
+    Updated(String),
+    Unchanged(String),
+    Created(String),
+    Skipped(&'static str),
+    Failed(io::Error),
+}
+
+/// Options of `study import ics`.
+pub struct IcsOptions<'a> {
+    pub exercise_type: Option<&'a str>,
+    pub create: bool,
+    pub dry_run: bool,
+}
+
+/// Copy the deadlines of the events and to-dos in `file` into the course's exercises.
+pub fn ics(
+    workspace: &Workspace,
+    file: &Path,
+    course: &str,
+    options: IcsOptions,
+) -> io::Result<()> {
+    let text = std::fs::read_to_string(file)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", file.display(), e)))?;
+    let mut entries = ics::parse(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", file.display(), e),
+        )
+    })?;
+    // Exercises created from the calendar are numbered in deadline order.
+    entries.sort_by_key(|entry| entry.due);
+
+    let course = workspace.find_course(course, false)?;
+    let config = course.config();
+    // Fail early on a bad --type, or on a missing one if exercises may be created.
+    if options.create || options.exercise_type.is_some() {
+        course.exercise_type(options.exercise_type)?;
+    }
+
+    let mut exercises: Vec<Exercise> = Vec::new();
+    for dir in exercise_dirs(course.dir()) {
+        let Ok(meta) = load_meta(&dir) else {
+            continue;
+        };
+        let info = meta.exercise;
+        let number = config
+            .exercise_types
+            .get(&info.exercise_type)
+            .and_then(|t| t.naming_scheme.number(&info.name));
+        exercises.push(Exercise {
+            title: info
+                .extra
+                .get("title")
+                .and_then(|title| title.as_str())
+                .map(String::from),
+            uid: info
+                .extra
+                .get(UID_KEY)
+                .and_then(|uid| uid.as_str())
+                .map(String::from),
+            dir,
+            name: info.name,
+            exercise_type: info.exercise_type,
+            number,
+            due: info.due,
+        });
+    }
+
+    let mut rows = Vec::new();
+    for entry in &entries {
+        let outcome = match entry.due {
+            None => Outcome::Skipped("no date"),
+            Some(due) => match find_exercise(&exercises, entry, options.exercise_type) {
+                Ok(Some(index)) => {
+                    let exercise = &mut exercises[index];
+                    let unchanged = exercise.due == Some(due)
+                        && (entry.uid.is_none() || exercise.uid == entry.uid);
+                    if unchanged {
+                        Outcome::Unchanged(exercise.name.clone())
+                    } else if !options.dry_run
+                        && let Err(e) =
+                            edit_meta(&exercise.dir, |doc| set_due(doc, entry, due, false))
+                    {
+                        Outcome::Failed(e.into())
+                    } else {
+                        exercise.due = Some(due);
+                        exercise.uid = entry.uid.clone().or(exercise.uid.take());
+                        Outcome::Updated(exercise.name.clone())
+                    }
+                }
+                Ok(None) if options.create && options.dry_run => {
+                    Outcome::Created("(new)".to_string())
+                }
+                Ok(None) if options.create => {
+                    let new = NewExercise {
+                        exercise_type: options.exercise_type,
+                        due: Some(due),
+                        ..Default::default()
+                    };
+                    match workspace.create_exercise(&course, new).and_then(|created| {
+                        let exercise = created.exercise;
+                        exercise.edit_meta(|doc| set_due(doc, entry, due, true))?;
+                        Ok(exercise)
+                    }) {
+                        Ok(exercise) => Outcome::Created(exercise.name().to_string()),
+                        Err(e) => Outcome::Failed(e.into()),
+                    }
+                }
+                Ok(None) => Outcome::Skipped("no matching exercise"),
+                Err(reason) => Outcome::Skipped(reason),
+            },
+        };
+        rows.push((entry, outcome));
+    }
+
+    print_rows(&rows, options.dry_run);
+
+    // Report failures at the end rather than leaving the import half done.
+    let mut failed = 0;
+    for (entry, outcome) in &rows {
+        if let Outcome::Failed(e) = outcome {
+            failed += 1;
+            eprintln!("error: '{}': {}", entry.summary, e);
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(io::Error::other(format!(
+            "{} of {} calendar entries could not be imported",
+            n,
+            rows.len()
+        ))),
+    }
+}
+
+/// The exercise `entry` is about: the one imported from it before, one whose
+/// name or title appears in its summary, or the one with the number it mentions.
+fn find_exercise(
+    exercises: &[Exercise],
+    entry: &Entry,
+    exercise_type: Option<&str>,
+) -> Result<Option<usize>, &'static str> {
+    if let Some(uid) = &entry.uid
+        && let Some(index) = exercises.iter().position(|e| e.uid.as_ref() == Some(uid))
+    {
+        return Ok(Some(index));
+    }
+
+    let summary = entry.summary.to_lowercase();
+    let mentioned: Vec<(usize, usize)> = exercises
+        .iter()
+        .enumerate()
+        .filter_map(|(index, exercise)| {
+            let length = std::iter::once(&exercise.name)
+                .chain(&exercise.title)
+                .map(|key| key.to_lowercase())
+                .filter(|key| mentions(&summary, key))
+                .map(|key| key.len())
+                .max()?;
+            Some((index, length))
+        })
+        .collect();
+    // The longest mention wins, e.g. a title over a shorter name inside it.
+    if let Some(longest) = mentioned.iter().map(|&(_, length)| length).max() {
+        let best: Vec<usize> = mentioned
+            .iter()
+            .filter(|&&(_, length)| length == longest)
+            .map(|&(index, _)| index)
+            .collect();
+        return match best[..] {
+            [index] => Ok(Some(index)),
+            _ => Err("matches several exercises"),
+        };
+    }
+
+    let Some(number) = summary_number(&entry.summary) else {
+        return Ok(None);
+    };
+    let numbered: Vec<usize> = exercises
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.number == Some(number))
+        .filter(|(_, e)| exercise_type.is_none_or(|t| e.exercise_type == t))
+        .map(|(index, _)| index)
+        .collect();
+    match numbered[..] {
+        [] => Ok(None),
+        [index] => Ok(Some(index)),
+        _ => Err("number matches several exercises, use --type"),
+    }
+}
+
+/// Whether `key` appears in `summary` as a whole word or words, so that
+/// `hw1` is not found in `hw10 deadline`.
+fn mentions(summary: &str, key: &str) -> bool {
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    !key.is_empty()
+        && summary.match_indices(key).any(|(start, _)| {
+            !is_word(summary[..start].chars().next_back())
+                && !is_word(summary[start + key.len()..].chars().next())
+        })
+}
+
+/// The first standalone number in `summary`, e.g. 3 in `Exercise 3 deadline`.
+/// Digits inside words such as course codes are not counted.
+fn summary_number(summary: &str) -> Option<u32> {
+    summary
+        .split(|c: char| !c.is_alphanumeric())
+        .find(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()))
+        .and_then(|word| word.parse().ok())
+}
+
+fn set_due(doc: &mut toml_edit::DocumentMut, entry: &Entry, due: NaiveDate, created: bool) {
+    let exercise = &mut doc["exercise"];
+    exercise["due"] = value(due.to_string());
+    if let Some(uid) = &entry.uid {
+        exercise[UID_KEY] = value(uid.as_str());
+    }
+    if created && !entry.summary.is_empty() {
+        exercise["title"] = value(entry.summary.as_str());
+    }
+}
+
+fn print_rows(rows: &[(&Entry, Outcome)], dry_run: bool) {
+    if rows.is_empty() {
+        println!("No events or to-dos found.");
+        return;
+    }
+
+    let (mut updated, mut created, mut skipped, mut failed) = (0, 0, 0, 0);
+    let columns: Vec<(&str, String, String)> = rows
+        .iter()
+        .map(|(entry, outcome)| {
+            let (action, exercise) = match outcome {
+                Outcome::Updated(name) => {
+                    updated += 1;
+                    ("update", name.clone())
+                }
+                Outcome::Unchanged(name) => ("same", name.clone()),
+                Outcome::Created(name) => {
+                    created += 1;
+                    ("create", name.clone())
+                }
+                Outcome::Skipped(reason) => {
+                    skipped += 1;
+                    ("skip", format!("({})", reason))
+                }
+                Outcome::Failed(_) => {
+                    failed += 1;
+                    ("fail", "(see below)".to_string())
+                }
+            };
+            let due = entry.due.map(|d| d.to_string()).unwrap_or_default();
+            (action, exercise, due)
+        })
+        .collect();
+    let exercise_width = columns.iter().map(|(_, e, _)| e.len()).max().unwrap_or(0);
+
+    for ((entry, _), (action, exercise, due)) in rows.iter().zip(&columns) {
+        println!(
+            "{:<6}  {:<exercise_width$}  {:<10}  {}",
+            action, exercise, due, entry.summary
+        );
+    }
+    println!(
+        "{}{} updated, {} created, {} skipped{}.",
+        if dry_run { "Dry run: " } else { "" },
+        updated,
+        created,
+        skipped,
+        if failed > 0 { format!(", {} failed", failed) } else { String::new() }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_whole_words_only() {
+        assert!(mentions("ms-c2286 ex1 deadline", "ex1"));
+        assert!(!mentions("ms-c2286 ex10 deadline", "ex1"));
+        assert!(mentions("ms-c2286 ex10 deadline", "ex10"));
+        assert!(mentions("ex1: ex10 and ex1", "ex1"));
+        assert!(mentions("deadline: linear maps", "linear maps"));
+        assert!(!mentions("nonlinear maps", "linear maps"));
+        assert!(!mentions("anything", ""));
+    }
+
+    #[test]
+    fn summary_number_skips_course_codes() {
+        assert_eq!(summary_number("MS-C2286 Exercise 3 deadline"), Some(3));
+        assert_eq!(summary_number("Round 10/12"), Some(10));
+        assert_eq!(summary_number("CS-E4000 project"), None);
+    }
+}
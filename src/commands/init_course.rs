@@ -1,31 +1,68 @@
-use crate::config::{
-    CourseConfig, CourseInfo, GlobalConfig, StudyConfig, expand_tilde, parse_course_code,
-    save_course_config,
-};
 use std::collections::HashMap;
 use std::io::{self, Write};
-
-pub fn run(global: &GlobalConfig, name: Option<&str>, raw: bool) -> io::Result<()> {
+use study::config::{
+    CONFIG_VERSION, CourseConfig, CourseInfo, ExerciseType, GlobalConfig, StudyConfig,
+    course_dir_for, list_presets, load_preset, parse_course_code, save_course_config,
+};
+use study::course_code::UNDEFINED_FACULTY;
+use study::naming::NamingScheme;
+
+/// Create a course directory and its `course.toml`.
+///
+/// Without a course code the details are prompted for interactively,
+/// including a preset and exercise types. `types` are `name=template[:scheme]`
+/// specs applied on top of the preset.
+pub fn run(
+    global: &GlobalConfig,
+    name: Option<&str>,
+    raw: bool,
+    title: Option<&str>,
+    preset: Option<&str>,
+    types: &[String],
+) -> io::Result<()> {
+    let interactive = name.is_none();
     let code = match name {
         Some(n) => n.to_string(),
         None => prompt_course_details()?,
     };
 
-    let course_name = name
-        .is_none()
-        .then(|| prompt_course_name())
-        .transpose()?
-        .flatten();
+    let course_name = match title {
+        Some(title) => Some(title.to_string()),
+        None if interactive => prompt_course_name()?,
+        None => None,
+    };
 
-    let courses_dir = expand_tilde(&global.courses_dir);
+    let preset = match preset {
+        Some(p) => Some(p.to_string()),
+        None if interactive => prompt_preset()?,
+        None => None,
+    };
+    let preset = preset.as_deref().map(load_preset).transpose()?;
+
+    let mut exercise_types = HashMap::new();
+    let mut template_dir = None;
+    let mut study = StudyConfig::default();
+    if let Some(preset) = preset {
+        exercise_types = preset.exercise_types;
+        template_dir = preset.template_dir;
+        study = preset.study;
+    }
+    for spec in types {
+        let (type_name, ex_type) = parse_type_spec(spec)?;
+        exercise_types.insert(type_name, ex_type);
+    }
+    if interactive {
+        prompt_exercise_types(&mut exercise_types)?;
+    }
 
-    let (faculty, dir_name) = if raw {
-        ("undefined".to_string(), code.clone())
+    let split = if raw {
+        (UNDEFINED_FACULTY.to_string(), code.clone())
     } else {
-        parse_course_code(&code)
+        let parsed = parse_course_code(global, &code);
+        (parsed.faculty, parsed.number)
     };
 
-    let course_dir = courses_dir.join(&faculty).join(&dir_name);
+    let course_dir = course_dir_for(global, &code, split);
 
     if course_dir.exists() {
         return Err(io::Error::new(
@@ -37,26 +74,127 @@ pub fn run(global: &GlobalConfig, name: Option<&str>, raw: bool) -> io::Result<(
     std::fs::create_dir_all(&course_dir)?;
 
     let config = CourseConfig {
+        version: CONFIG_VERSION,
         course: CourseInfo {
             code: code.clone(),
             name: course_name,
+            aliases: Vec::new(),
+            term: global.active_term.clone(),
+            archived: false,
         },
-        template_dir: None,
-        exercise_types: HashMap::new(),
-        study: StudyConfig::default(),
+        template_dir,
+        exercise_types,
+        study,
     };
 
     save_course_config(&course_dir, &config)?;
 
     println!("Initialized course '{}' at {}", code, course_dir.display());
-    println!(
-        "Edit {} to configure exercise types and study commands.",
-        course_dir.join("course.toml").display()
-    );
+    if config.exercise_types.is_empty() {
+        println!(
+            "Edit {} to configure exercise types and study commands.",
+            course_dir.join("course.toml").display()
+        );
+    } else {
+        let mut names: Vec<&str> = config.exercise_types.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        println!("Exercise types: {}", names.join(", "));
+    }
 
     Ok(())
 }
 
+/// Parse `name=template[:scheme]`. The scheme defaults to `<name>{:02}`.
+fn parse_type_spec(spec: &str) -> io::Result<(String, ExerciseType)> {
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Invalid exercise type '{}': {} (expected name=template[:scheme])",
+                spec, reason
+            ),
+        )
+    };
+
+    let (name, rest) = spec.split_once('=').ok_or_else(|| invalid("missing '='"))?;
+    let (template, scheme) = match rest.split_once(':') {
+        Some((template, scheme)) => (template, Some(scheme)),
+        None => (rest, None),
+    };
+    if name.is_empty() || template.is_empty() {
+        return Err(invalid("name and template are required"));
+    }
+
+    Ok((name.to_string(), exercise_type(name, template, scheme)?))
+}
+
+fn exercise_type(name: &str, template: &str, scheme: Option<&str>) -> io::Result<ExerciseType> {
+    let scheme = scheme.map_or_else(|| format!("{}{{:02}}", name), str::to_string);
+    let naming_scheme: NamingScheme = scheme
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    Ok(ExerciseType {
+        template: template.to_string(),
+        naming_scheme,
+        meta: HashMap::new(),
+        study: StudyConfig::default(),
+        submit: None,
+    })
+}
+
+fn prompt(label: &str) -> io::Result<String> {
+    print!("{}", label);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+fn prompt_preset() -> io::Result<Option<String>> {
+    let presets = list_presets();
+    if presets.is_empty() {
+        return Ok(None);
+    }
+
+    println!("Presets:");
+    for name in &presets {
+        match load_preset(name).ok().and_then(|p| p.description) {
+            Some(description) => println!("  {:<20} {}", name, description),
+            None => println!("  {}", name),
+        }
+    }
+    let answer = prompt("Preset (leave empty for none): ")?;
+    Ok((!answer.is_empty()).then_some(answer))
+}
+
+fn prompt_exercise_types(exercise_types: &mut HashMap<String, ExerciseType>) -> io::Result<()> {
+    loop {
+        let name = prompt("Add exercise type (leave empty to finish): ")?;
+        if name.is_empty() {
+            return Ok(());
+        }
+
+        let template = prompt(&format!("  Template for '{}' [empty]: ", name))?;
+        let template = if template.is_empty() {
+            "empty".to_string()
+        } else {
+            template
+        };
+
+        let default_scheme = format!("{}{{:02}}", name);
+        let scheme = prompt(&format!("  Naming scheme [{}]: ", default_scheme))?;
+        let scheme = (!scheme.is_empty()).then_some(scheme);
+
+        match exercise_type(&name, &template, scheme.as_deref()) {
+            Ok(ex_type) => {
+                exercise_types.insert(name, ex_type);
+            }
+            Err(e) => eprintln!("  {}", e),
+        }
+    }
+}
+
 fn prompt_course_details() -> io::Result<String> {
     print!("Course code: ");
     io::stdout().flush()?;
@@ -0,0 +1,186 @@
+use super::print_structured;
+use crate::cli::OutputFormat;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::io;
+use std::path::PathBuf;
+use study::{Course, ExerciseStatus, Workspace};
+
+#[derive(Serialize)]
+struct CourseList {
+    courses: Vec<CourseEntry>,
+}
+
+#[derive(Serialize)]
+struct CourseEntry {
+    faculty: String,
+    code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    term: Option<String>,
+    archived: bool,
+    path: PathBuf,
+    exercises: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_exercise: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ExerciseList {
+    exercises: Vec<ExerciseEntry>,
+}
+
+#[derive(Serialize)]
+struct ExerciseEntry {
+    name: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    exercise_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<ExerciseStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due: Option<NaiveDate>,
+    path: PathBuf,
+}
+
+pub fn run(
+    workspace: &Workspace,
+    course: Option<&str>,
+    all: bool,
+    format: OutputFormat,
+) -> io::Result<()> {
+    match course {
+        Some(name) => {
+            let list = ExerciseList {
+                exercises: collect_exercises(&workspace.find_course(name, all)?),
+            };
+            match format {
+                OutputFormat::Text => print_exercises(&list.exercises),
+                _ => print_structured(&list, format)?,
+            }
+        }
+        None => {
+            let list = CourseList {
+                courses: collect_courses(workspace, all),
+            };
+            match format {
+                OutputFormat::Text => print_courses(&list.courses),
+                _ => print_structured(&list, format)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn collect_courses(workspace: &Workspace, all: bool) -> Vec<CourseEntry> {
+    let state = workspace.state();
+    let mut courses: Vec<CourseEntry> = workspace
+        .courses(all)
+        .into_iter()
+        .map(|course| {
+            let info = &course.config().course;
+            CourseEntry {
+                faculty: workspace.faculty(&course),
+                last_exercise: state.last_exercises.get(&info.code).cloned(),
+                archived: workspace.is_archived(&course),
+                code: info.code.clone(),
+                name: info.name.clone(),
+                aliases: info.aliases.clone(),
+                term: info.term.clone(),
+                exercises: course.exercises().len(),
+                path: course.dir().to_path_buf(),
+            }
+        })
+        .collect();
+    courses.sort_by(|a, b| (&a.faculty, &a.code).cmp(&(&b.faculty, &b.code)));
+    courses
+}
+
+fn collect_exercises(course: &Course) -> Vec<ExerciseEntry> {
+    let mut exercises: Vec<ExerciseEntry> = course
+        .exercises()
+        .into_iter()
+        .map(|exercise| {
+            let meta = exercise.meta().ok().map(|m| m.exercise);
+            ExerciseEntry {
+                name: exercise.name().to_string(),
+                status: meta.as_ref().map(|m| m.status),
+                due: meta.as_ref().and_then(|m| m.due),
+                exercise_type: meta.map(|m| m.exercise_type),
+                path: exercise.dir().to_path_buf(),
+            }
+        })
+        .collect();
+    exercises.sort_by(|a, b| a.name.cmp(&b.name));
+    exercises
+}
+
+fn print_courses(courses: &[CourseEntry]) {
+    if courses.is_empty() {
+        println!("No courses found.");
+        return;
+    }
+
+    let faculty_width = courses.iter().map(|c| c.faculty.len()).max().unwrap_or(0);
+    let code_width = courses.iter().map(|c| c.code.len()).max().unwrap_or(0);
+    let name_width = courses
+        .iter()
+        .map(|c| c.name.as_deref().unwrap_or("").len())
+        .max()
+        .unwrap_or(0);
+
+    for course in courses {
+        let mut details = vec![format!("{} exercise(s)", course.exercises)];
+        details.extend(
+            course
+                .last_exercise
+                .as_deref()
+                .map(|ex| format!("last: {}", ex)),
+        );
+        details.extend(course.term.as_deref().map(|term| format!("term: {}", term)));
+        if course.archived {
+            details.push("archived".to_string());
+        }
+        println!(
+            "{:<faculty_width$}  {:<code_width$}  {:<name_width$}  {}  {}",
+            course.faculty,
+            course.code,
+            course.name.as_deref().unwrap_or(""),
+            details.join(", "),
+            course.path.display(),
+        );
+    }
+}
+
+fn print_exercises(exercises: &[ExerciseEntry]) {
+    if exercises.is_empty() {
+        println!("No exercises found.");
+        return;
+    }
+
+    let name_width = exercises.iter().map(|e| e.name.len()).max().unwrap_or(0);
+    let type_width = exercises
+        .iter()
+        .map(|e| e.exercise_type.as_deref().unwrap_or("-").len())
+        .max()
+        .unwrap_or(0);
+
+    let status = |e: &ExerciseEntry| e.status.map_or("-".to_string(), |s| s.to_string());
+    let status_width = exercises.iter().map(|e| status(e).len()).max().unwrap_or(0);
+
+    for exercise in exercises {
+        let due = exercise
+            .due
+            .map_or("-".to_string(), |due| format!("due {}", due));
+        println!(
+            "{:<name_width$}  {:<type_width$}  {:<status_width$}  {:<14}  {}",
+            exercise.name,
+            exercise.exercise_type.as_deref().unwrap_or("-"),
+            status(exercise),
+            due,
+            exercise.path.display(),
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use super::{course_code, print_structured};
+use crate::cli::OutputFormat;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::io;
+use study::Workspace;
+use study::history::{format_duration, load_history};
+
+#[derive(Serialize)]
+struct SessionLog {
+    sessions: Vec<SessionEntry>,
+}
+
+#[derive(Serialize)]
+struct SessionEntry {
+    course: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exercise: Option<String>,
+    start: DateTime<Local>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<DateTime<Local>>,
+    seconds: i64,
+    running: bool,
+}
+
+pub fn run(
+    workspace: &Workspace,
+    course: Option<&str>,
+    limit: Option<usize>,
+    format: OutputFormat,
+) -> io::Result<()> {
+    let code = course
+        .map(|input| course_code(workspace, input))
+        .transpose()?;
+
+    let sessions: Vec<SessionEntry> = load_history()?
+        .into_iter()
+        .rev()
+        .filter(|record| code.as_ref().is_none_or(|code| &record.course == code))
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|record| SessionEntry {
+            seconds: record.duration().num_seconds(),
+            running: record.running(),
+            course: record.course,
+            exercise: record.exercise,
+            start: record.start,
+            end: record.end,
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Text => print_sessions(&sessions),
+        _ => print_structured(&SessionLog { sessions }, format)?,
+    }
+    Ok(())
+}
+
+fn print_sessions(sessions: &[SessionEntry]) {
+    if sessions.is_empty() {
+        println!("No study sessions recorded.");
+        return;
+    }
+
+    let durations: Vec<String> = sessions
+        .iter()
+        .map(|s| format_duration(chrono::TimeDelta::seconds(s.seconds)))
+        .collect();
+    let duration_width = durations.iter().map(String::len).max().unwrap_or(0);
+
+    for (session, duration) in sessions.iter().zip(&durations) {
+        let target = match &session.exercise {
+            Some(exercise) => format!("{} / {}", session.course, exercise),
+            None => session.course.clone(),
+        };
+        let status = match (session.end, session.running) {
+            (None, true) => "  (running)",
+            (None, false) => "  (unfinished)",
+            _ => "",
+        };
+        println!(
+            "{}  {:>duration_width$}  {}{}",
+            session.start.format("%Y-%m-%d %H:%M"),
+            duration,
+            target,
+            status,
+        );
+    }
+}
@@ -1,4 +1,218 @@
+use crate::cli::{
+    Args, Command, ExportTarget, ImportSource, InitTarget, OutputFormat, TemplateAction,
+};
+use ::study::Workspace;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::io::{self, IsTerminal};
+
+pub mod archive;
 pub mod completions;
+pub mod doctor;
+pub mod due;
+pub mod export;
 pub mod init_course;
+pub mod import;
 pub mod init_exercise;
+pub mod list;
+pub mod log;
+pub mod pack;
+pub mod parse_code;
+pub mod pick;
+pub mod schema;
+mod session;
+pub mod stats;
+pub mod status;
+pub mod stop;
 pub mod study;
+pub mod template;
+
+/// Run the command given on the command line. `study doctor` is run
+/// separately, before the workspace is loaded.
+pub fn run(workspace: &Workspace, args: Args) -> io::Result<()> {
+    match args.command {
+        Some(Command::Init { target }) => match target {
+            InitTarget::Course {
+                name,
+                raw,
+                title,
+                preset,
+                types,
+            } => init_course::run(
+                workspace.global(),
+                name.as_deref(),
+                raw,
+                title.as_deref(),
+                preset.as_deref(),
+                &types,
+            ),
+            InitTarget::Exercise {
+                r#type,
+                name,
+                course,
+                due,
+            } => init_exercise::run(
+                workspace,
+                r#type.as_deref(),
+                name.as_deref(),
+                course.as_deref(),
+                due,
+            ),
+        },
+        Some(Command::Study {
+            course,
+            exercise,
+            wait,
+            all,
+        }) => study::run(workspace, Some(&course), exercise.as_deref(), wait, all),
+        Some(Command::Stop) => stop::run(workspace),
+        Some(Command::Status {
+            exercise,
+            status,
+            course,
+            due,
+            points,
+            grade,
+            tags,
+            untags,
+        }) => status::run(
+            workspace,
+            &exercise,
+            course.as_deref(),
+            status::Update {
+                status,
+                due,
+                points,
+                grade,
+                tags,
+                untags,
+            },
+        ),
+        Some(Command::Pack {
+            exercise,
+            course,
+            output,
+            no_build,
+            dry_run,
+        }) => pack::run(
+            workspace,
+            exercise.as_deref(),
+            course.as_deref(),
+            pack::Options {
+                output: output.as_deref(),
+                build: !no_build,
+                dry_run,
+            },
+        ),
+        Some(Command::Due { all, json, format }) => {
+            let format = if json { OutputFormat::Json } else { format };
+            due::run(workspace, all, format)
+        }
+        Some(Command::Log {
+            course,
+            limit,
+            json,
+            format,
+        }) => {
+            let format = if json { OutputFormat::Json } else { format };
+            log::run(workspace, course.as_deref(), limit, format)
+        }
+        Some(Command::Stats {
+            course,
+            json,
+            format,
+        }) => {
+            let format = if json { OutputFormat::Json } else { format };
+            stats::run(workspace, course.as_deref(), format)
+        }
+        Some(Command::WatchSession { id }) => ::study::history::watch(id),
+        Some(Command::List {
+            course,
+            all,
+            json,
+            format,
+        }) => {
+            let format = if json { OutputFormat::Json } else { format };
+            list::run(workspace, course.as_deref(), all, format)
+        }
+        Some(Command::Archive { course, term }) => {
+            archive::run(workspace, course.as_deref(), term.as_deref())
+        }
+        Some(Command::ParseCode { input, raw }) => parse_code::run(workspace.global(), &input, raw),
+        Some(Command::Import { source }) => match source {
+            ImportSource::Ics {
+                file,
+                course,
+                r#type,
+                create,
+                dry_run,
+            } => import::ics(
+                workspace,
+                &file,
+                &course,
+                import::IcsOptions {
+                    exercise_type: r#type.as_deref(),
+                    create,
+                    dry_run,
+                },
+            ),
+        },
+        Some(Command::Export { target }) => match target {
+            ExportTarget::Ics { output, all } => {
+                export::ics(workspace, output.as_deref(), all)
+            }
+        },
+        Some(Command::Template { action }) => match action {
+            TemplateAction::List { course } => template::list(workspace, course.as_deref()),
+            TemplateAction::Show { name, course } => {
+                template::show(workspace, &name, course.as_deref())
+            }
+        },
+        Some(Command::Doctor { .. }) => unreachable!("handled before loading the config"),
+        Some(Command::Schema { file }) => schema::run(file),
+        Some(Command::Completions { shell }) => completions::run(shell),
+        None if args.course.is_none()
+            && args.exercise.is_none()
+            && io::stdin().is_terminal()
+            && io::stdout().is_terminal() =>
+        {
+            pick::run(workspace, args.wait, args.all)
+        }
+        None => {
+            study::run(
+                workspace,
+                args.course.as_deref(),
+                args.exercise.as_deref(),
+                args.wait,
+                args.all,
+            )
+        }
+    }
+}
+
+/// Print `value` as JSON or TOML.
+fn print_structured<T: Serialize>(value: &T, format: OutputFormat) -> io::Result<()> {
+    let output = match format {
+        OutputFormat::Toml => toml::to_string_pretty(value).map_err(io::Error::other)?,
+        _ => serde_json::to_string_pretty(value).map_err(io::Error::other)?,
+    };
+    println!("{}", output.trim_end());
+    Ok(())
+}
+
+/// The code of the course named `input`, archived courses included.
+fn course_code(workspace: &Workspace, input: &str) -> io::Result<String> {
+    Ok(workspace.find_course(input, true)?.code().to_string())
+}
+
+/// A deadline relative to today: `today`, `in 3 days`, `2 days ago`.
+fn relative_due(due: NaiveDate) -> String {
+    let days = (due - chrono::Local::now().date_naive()).num_days();
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        -1 => "yesterday".to_string(),
+        d if d > 0 => format!("in {} days", d),
+        d => format!("{} days ago", -d),
+    }
+}
@@ -0,0 +1,295 @@
+use super::study::{build_command, command_line, command_vars, condition_holds, expand_spec};
+use chrono::{Datelike, Local, Timelike};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use study::config::{ArchiveFormat, ExerciseStatus, SubmitConfig, edit_meta, load_meta};
+use study::template::expand;
+use study::{Course, Workspace};
+use toml_edit::value;
+use zip::write::SimpleFileOptions;
+
+/// Every archive format, to recognise earlier submissions in either.
+const FORMATS: [ArchiveFormat; 2] = [ArchiveFormat::Zip, ArchiveFormat::TarGz];
+
+/// Options of `study pack`.
+pub struct Options<'a> {
+    pub output: Option<&'a Path>,
+    pub build: bool,
+    pub dry_run: bool,
+}
+
+/// Build and archive an exercise for submission, then mark it submitted.
+pub fn run(
+    workspace: &Workspace,
+    exercise: Option<&str>,
+    course: Option<&str>,
+    options: Options,
+) -> io::Result<()> {
+    let course = workspace.course_or_cwd(course)?;
+    let (course_dir, config) = (course.dir().to_path_buf(), course.config());
+    let exercise_dir = match exercise {
+        Some(input) => course.find_exercise(input)?.dir().to_path_buf(),
+        None => current_exercise(workspace, &course)?,
+    };
+    let meta = load_meta(&exercise_dir)?;
+    let submit = config
+        .exercise_types
+        .get(&meta.exercise.exercise_type)
+        .and_then(|t| t.submit.clone())
+        .unwrap_or_default();
+
+    let mut vars = command_vars(
+        &config.course,
+        &course_dir,
+        &exercise_dir,
+        Some(&meta.exercise.name),
+        Some(&meta),
+    );
+    vars.insert("date".into(), Local::now().format("%Y-%m-%d").to_string());
+    let output = match options.output {
+        Some(path) => std::env::current_dir()?.join(path),
+        None => exercise_dir.join(archive_name(&submit, &expand(&submit.output, &vars))),
+    };
+
+    if options.build && !options.dry_run {
+        for command in &submit.build {
+            let spec = expand_spec(command.spec(), &vars);
+            if !condition_holds(&spec, &exercise_dir, Some(&meta.exercise.exercise_type)) {
+                continue;
+            }
+            let line = command_line(&spec.run, &vars)?;
+            println!("  Running: {}", line);
+            let status = build_command(&spec, &exercise_dir, &vars)?.status()?;
+            if !status.success() {
+                return Err(io::Error::other(format!(
+                    "Build command '{}' failed ({})",
+                    line, status
+                )));
+            }
+        }
+    }
+
+    let files = collect_files(&exercise_dir, &submit, &output)?;
+    if files.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "No files in {} match the submission globs.",
+                exercise_dir.display()
+            ),
+        ));
+    }
+
+    if options.dry_run {
+        for file in &files {
+            println!("{}", file.display());
+        }
+        println!("Would pack {} files into {}", files.len(), output.display());
+        return Ok(());
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match submit.format {
+        ArchiveFormat::Zip => write_zip(&exercise_dir, &files, &output)?,
+        ArchiveFormat::TarGz => write_tar_gz(&exercise_dir, &files, &output)?,
+    }
+    println!("Packed {} files into {}", files.len(), output.display());
+
+    let submitted = Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    edit_meta(&exercise_dir, |doc| {
+        let exercise = &mut doc["exercise"];
+        exercise["status"] = value(ExerciseStatus::Submitted.to_string());
+        exercise["submitted"] = value(submitted);
+        exercise["submission"] = value(output.display().to_string());
+    })?;
+    Ok(())
+}
+
+/// The exercise containing the current directory, or else the one studied last.
+fn current_exercise(workspace: &Workspace, course: &Course) -> io::Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    if let Some(exercise) = course
+        .exercises()
+        .into_iter()
+        .find(|e| cwd.starts_with(e.dir()))
+    {
+        return Ok(exercise.dir().to_path_buf());
+    }
+    workspace
+        .state()
+        .last_exercises
+        .get(course.code())
+        .map(|name| course.dir().join(name))
+        .filter(|dir| dir.is_dir())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "No exercise specified and no previous exercise found.",
+            )
+        })
+}
+
+fn archive_name(submit: &SubmitConfig, name: &str) -> String {
+    let extension = format!(".{}", submit.format.extension());
+    if name.ends_with(&extension) {
+        name.to_string()
+    } else {
+        format!("{}{}", name, extension)
+    }
+}
+
+fn glob_set(patterns: &[String]) -> io::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob: Glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Globs for every archive `submit.output` can name, whatever the placeholder
+/// values and format, e.g. `*_*.zip` and `*_*.tar.gz` for the default.
+fn archive_globs(submit: &SubmitConfig) -> io::Result<GlobSet> {
+    let mut stem = String::new();
+    let mut rest = submit.output.as_str();
+    while let Some((start, end)) = rest
+        .find('{')
+        .and_then(|start| Some((start, start + rest[start..].find('}')?)))
+    {
+        stem.push_str(&globset::escape(&rest[..start]));
+        if !stem.ends_with('*') {
+            stem.push('*');
+        }
+        rest = &rest[end + 1..];
+    }
+    stem.push_str(&globset::escape(rest));
+    let stem = FORMATS
+        .iter()
+        .find_map(|format| stem.strip_suffix(&format!(".{}", format.extension())))
+        .unwrap_or(&stem);
+    let patterns: Vec<String> = FORMATS
+        .iter()
+        .map(|format| format!("{}.{}", stem, format.extension()))
+        .collect();
+    glob_set(&patterns)
+}
+
+/// Files to pack, relative to `exercise_dir` and sorted. The archive itself and
+/// earlier ones named by `submit.output` are never included.
+fn collect_files(
+    exercise_dir: &Path,
+    submit: &SubmitConfig,
+    output: &Path,
+) -> io::Result<Vec<PathBuf>> {
+    let include = glob_set(&submit.include)?;
+    let exclude = glob_set(&submit.exclude)?;
+    let archives = archive_globs(submit)?;
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        for entry in fs::read_dir(exercise_dir.join(&relative))? {
+            let entry = entry?;
+            let path = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                if !exclude.is_match(&path) {
+                    pending.push(path);
+                }
+            } else if include.is_match(&path)
+                && !exclude.is_match(&path)
+                && !archives.is_match(&path)
+                && entry.path() != output
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn write_zip(exercise_dir: &Path, files: &[PathBuf], output: &Path) -> io::Result<()> {
+    let mut zip = zip::ZipWriter::new(File::create(output)?);
+    for file in files {
+        let source = exercise_dir.join(file);
+        let metadata = fs::metadata(&source)?;
+        let mut options = SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .unix_permissions(std::os::unix::fs::PermissionsExt::mode(
+                &metadata.permissions(),
+            ));
+        if let Some(time) = metadata.modified().ok().and_then(zip_time) {
+            options = options.last_modified_time(time);
+        }
+        zip.start_file(file.to_string_lossy(), options)
+            .map_err(io::Error::other)?;
+        io::copy(&mut File::open(&source)?, &mut zip)?;
+    }
+    zip.finish().map_err(io::Error::other)?;
+    Ok(())
+}
+
+/// A file's modification time in the local time zone, as zip stores it.
+fn zip_time(modified: std::time::SystemTime) -> Option<zip::DateTime> {
+    let time = chrono::DateTime::<Local>::from(modified);
+    zip::DateTime::from_date_and_time(
+        time.year().try_into().ok()?,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .ok()
+}
+
+fn write_tar_gz(exercise_dir: &Path, files: &[PathBuf], output: &Path) -> io::Result<()> {
+    let encoder = GzEncoder::new(File::create(output)?, Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    for file in files {
+        tar.append_path_with_name(exercise_dir.join(file), file)?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archives(output: &str) -> GlobSet {
+        archive_globs(&SubmitConfig {
+            output: output.into(),
+            ..SubmitConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn archive_globs_match_every_date_and_format() {
+        let globs = archives("{course.code}_{exercise.name}-{date}");
+        assert!(globs.is_match("MS-C2286_ex01-2026-10-17.zip"));
+        assert!(globs.is_match("MS-C2286_ex01-2026-10-18.tar.gz"));
+        assert!(!globs.is_match("MS-C2286_ex01-2026-10-17.pdf"));
+        assert!(!globs.is_match("data/MS-C2286_ex01-2026-10-17.zip"));
+    }
+
+    #[test]
+    fn archive_globs_keep_literal_text() {
+        let globs = archives("dist/[final] {exercise.name}{date}.zip");
+        assert!(globs.is_match("dist/[final] ex01 2026.zip"));
+        assert!(globs.is_match("dist/[final] ex01.tar.gz"));
+        assert!(!globs.is_match("dist/f ex01.zip"));
+        assert!(!globs.is_match("[final] ex01.zip"));
+    }
+}
@@ -0,0 +1,51 @@
+use std::io;
+use study::config::{GlobalConfig, course_dir_for, parse_course_code};
+use study::course_code::UNDEFINED_FACULTY;
+
+/// Dry run of course code parsing: show each rule, which one matched and
+/// where `init course` would put the course.
+pub fn run(global: &GlobalConfig, input: &str, raw: bool) -> io::Result<()> {
+    let (faculty, number, rule) = if raw {
+        (
+            UNDEFINED_FACULTY.to_string(),
+            input.to_string(),
+            Some("--raw".to_string()),
+        )
+    } else {
+        let parsed = parse_course_code(global, input);
+        (parsed.faculty, parsed.number, parsed.rule)
+    };
+
+    if !raw {
+        let width = global
+            .code_rules
+            .iter()
+            .map(|r| r.name.len())
+            .max()
+            .unwrap_or(0);
+        let mut matched = false;
+        println!("Rules:");
+        for rule in &global.code_rules {
+            let status = if matched {
+                "skipped"
+            } else if rule.apply(input).is_some() {
+                matched = true;
+                "matched"
+            } else {
+                "no match"
+            };
+            println!("  {:<width$}  {:<8}  {}", rule.name, status, rule);
+        }
+        println!();
+    }
+
+    let dir = course_dir_for(global, input, (faculty.clone(), number.clone()));
+    println!(
+        "Rule:      {}",
+        rule.as_deref().unwrap_or("none (fallback)")
+    );
+    println!("Faculty:   {}", faculty);
+    println!("Number:    {}", number);
+    println!("Directory: {}", dir.display());
+    Ok(())
+}
@@ -0,0 +1,502 @@
+use crate::commands::{init_exercise, study};
+use ::study::config::StudyState;
+use ::study::{Course, Exercise, Workspace, rank};
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+use std::{fs, io};
+
+/// Full-screen course → exercise picker shown by `study` without arguments.
+pub fn run(workspace: &Workspace, wait: bool, all: bool) -> io::Result<()> {
+    let state = workspace.state();
+    let mut picker = Picker::new(load_courses(workspace, all, &state), state);
+
+    let mut terminal = ratatui::init();
+    let action = picker.run(&mut terminal);
+    ratatui::restore();
+
+    match action? {
+        Action::Quit => Ok(()),
+        Action::Study { course, exercise } => {
+            study::run_in(workspace, &picker.courses[course], exercise.as_ref(), wait)
+        }
+        Action::NewExercise {
+            course,
+            exercise_type,
+        } => {
+            let course = &picker.courses[course];
+            init_exercise::run_in(workspace, course, Some(&exercise_type), None, None)?;
+            study::run_in(workspace, course, None, wait)
+        }
+        Action::Open(dir) => open_dir(&dir),
+    }
+}
+
+/// What to do once the picker closes; courses are indices into `Picker::courses`.
+enum Action {
+    Quit,
+    Study {
+        course: usize,
+        exercise: Option<Exercise>,
+    },
+    NewExercise {
+        course: usize,
+        exercise_type: String,
+    },
+    Open(PathBuf),
+}
+
+struct ExerciseItem {
+    exercise: Exercise,
+    title: Option<String>,
+}
+
+enum Screen {
+    Courses,
+    Exercises {
+        course: usize,
+        exercises: Vec<ExerciseItem>,
+    },
+    Types {
+        course: usize,
+        types: Vec<String>,
+    },
+}
+
+struct Picker {
+    courses: Vec<Course>,
+    state: StudyState,
+    screen: Screen,
+    query: String,
+    /// Indices into the current screen's items that match `query`, best first.
+    visible: Vec<usize>,
+    list: ListState,
+    /// Course selection to restore when going back from an inner screen.
+    course_selection: Option<usize>,
+    message: Option<String>,
+}
+
+impl Picker {
+    fn new(courses: Vec<Course>, state: StudyState) -> Self {
+        let mut picker = Self {
+            courses,
+            state,
+            screen: Screen::Courses,
+            query: String::new(),
+            visible: Vec::new(),
+            list: ListState::default(),
+            course_selection: None,
+            message: None,
+        };
+        picker.refilter();
+        picker
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<Action> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            self.message = None;
+
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Char('c' | 'q') if ctrl => return Ok(Action::Quit),
+                KeyCode::Char('n') if ctrl => {
+                    if let Some(action) = self.new_exercise() {
+                        return Ok(action);
+                    }
+                }
+                KeyCode::Char('o') if ctrl => {
+                    if let Some(course) = self.current_course() {
+                        return Ok(Action::Open(self.courses[course].dir().to_path_buf()));
+                    }
+                }
+                KeyCode::Char(c) if !ctrl => {
+                    self.query.push(c);
+                    self.refilter();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.refilter();
+                }
+                KeyCode::Up => self.list.select_previous(),
+                KeyCode::Down => self.list.select_next(),
+                KeyCode::PageUp => self.list.scroll_up_by(10),
+                KeyCode::PageDown => self.list.scroll_down_by(10),
+                KeyCode::Home => self.list.select_first(),
+                KeyCode::End => self.list.select_last(),
+                KeyCode::Enter | KeyCode::Tab => {
+                    if let Some(action) = self.enter() {
+                        return Ok(action);
+                    }
+                }
+                KeyCode::Esc if !self.query.is_empty() => {
+                    self.query.clear();
+                    self.refilter();
+                }
+                KeyCode::Esc => match self.screen {
+                    Screen::Courses => return Ok(Action::Quit),
+                    _ => self.show_courses(),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.list
+            .selected()
+            .and_then(|i| self.visible.get(i))
+            .copied()
+    }
+
+    /// The course the selection belongs to.
+    fn current_course(&self) -> Option<usize> {
+        match &self.screen {
+            Screen::Courses => self.selected(),
+            Screen::Exercises { course, .. } | Screen::Types { course, .. } => Some(*course),
+        }
+    }
+
+    fn enter(&mut self) -> Option<Action> {
+        let selected = self.selected()?;
+        match &self.screen {
+            Screen::Courses => {
+                let exercises = load_exercises(&self.courses[selected], &self.state);
+                if exercises.is_empty() {
+                    return Some(Action::Study {
+                        course: selected,
+                        exercise: None,
+                    });
+                }
+                self.course_selection = self.list.selected();
+                self.set_screen(Screen::Exercises {
+                    course: selected,
+                    exercises,
+                });
+                None
+            }
+            Screen::Exercises { course, exercises } => Some(Action::Study {
+                course: *course,
+                exercise: Some(exercises[selected].exercise.clone()),
+            }),
+            Screen::Types { course, types } => Some(Action::NewExercise {
+                course: *course,
+                exercise_type: types[selected].clone(),
+            }),
+        }
+    }
+
+    fn new_exercise(&mut self) -> Option<Action> {
+        let course = self.current_course()?;
+        let config = self.courses[course].config();
+        let mut types: Vec<String> = config.exercise_types.keys().cloned().collect();
+        types.sort();
+        match types.len() {
+            0 => {
+                self.message = Some(format!(
+                    "{} has no exercise types in course.toml",
+                    config.course.code
+                ));
+                None
+            }
+            1 => Some(Action::NewExercise {
+                course,
+                exercise_type: types.swap_remove(0),
+            }),
+            _ => {
+                if matches!(self.screen, Screen::Courses) {
+                    self.course_selection = self.list.selected();
+                }
+                self.set_screen(Screen::Types { course, types });
+                None
+            }
+        }
+    }
+
+    fn show_courses(&mut self) {
+        self.set_screen(Screen::Courses);
+        self.list.select(self.course_selection.take().or(Some(0)));
+    }
+
+    fn set_screen(&mut self, screen: Screen) {
+        self.screen = screen;
+        self.query.clear();
+        self.refilter();
+    }
+
+    /// Recompute the visible items for the current query.
+    fn refilter(&mut self) {
+        let items: Vec<(usize, String, Vec<String>)> = match &self.screen {
+            Screen::Courses => self
+                .courses
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let course = &item.config().course;
+                    let keys = std::iter::once(course.code.clone())
+                        .chain(course.name.clone())
+                        .chain(course.aliases.iter().cloned())
+                        .collect();
+                    (i, String::new(), keys)
+                })
+                .collect(),
+            Screen::Exercises { exercises, .. } => exercises
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let keys = std::iter::once(item.exercise.name().to_string())
+                        .chain(item.title.clone())
+                        .collect();
+                    (i, String::new(), keys)
+                })
+                .collect(),
+            Screen::Types { types, .. } => types
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (i, String::new(), vec![name.clone()]))
+                .collect(),
+        };
+
+        self.visible = if self.query.is_empty() {
+            items.into_iter().map(|(i, _, _)| i).collect()
+        } else {
+            // Ties keep the recent-first order since labels are all empty.
+            rank(&self.query, items)
+                .into_iter()
+                .map(|c| c.value)
+                .collect()
+        };
+        self.list.select((!self.visible.is_empty()).then_some(0));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(body);
+
+        let title = match &self.screen {
+            Screen::Courses => "Courses".to_string(),
+            Screen::Exercises { course, .. } => {
+                format!("{} › Exercises", self.courses[*course].code())
+            }
+            Screen::Types { course, .. } => {
+                format!("{} › New exercise", self.courses[*course].code())
+            }
+        };
+        frame.render_widget(
+            Line::from(vec![
+                Span::from(format!("{}  ", title)).bold(),
+                Span::from("/ "),
+                Span::from(self.query.as_str()),
+                Span::from("▏").add_modifier(Modifier::SLOW_BLINK),
+            ]),
+            header,
+        );
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| ListItem::new(self.item_line(i)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered())
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("› ");
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        self.draw_preview(frame, preview_area);
+
+        let help = match &self.message {
+            Some(message) => Line::from(message.as_str()).red(),
+            None => Line::from(
+                "enter: select  esc: back  ctrl-n: new exercise  ctrl-o: open directory  ctrl-c: quit",
+            )
+            .dim(),
+        };
+        frame.render_widget(help, footer);
+    }
+
+    fn item_line(&self, index: usize) -> Line<'static> {
+        match &self.screen {
+            Screen::Courses => {
+                let course = &self.courses[index].config().course;
+                let mut spans = vec![Span::from(course.code.clone())];
+                if let Some(name) = &course.name {
+                    spans.push(Span::from(format!("  {}", name)).dim());
+                }
+                Line::from(spans)
+            }
+            Screen::Exercises { exercises, .. } => {
+                let item = &exercises[index];
+                let mut spans = vec![Span::from(item.exercise.name().to_string())];
+                if let Some(title) = &item.title {
+                    spans.push(Span::from(format!("  {}", title)).dim());
+                }
+                Line::from(spans)
+            }
+            Screen::Types { types, .. } => Line::from(types[index].clone()),
+        }
+    }
+
+    fn draw_preview(&self, frame: &mut Frame, area: Rect) {
+        let lines = match (&self.screen, self.selected()) {
+            (_, None) => Vec::new(),
+            (Screen::Courses, Some(i)) => self.course_preview(&self.courses[i]),
+            (Screen::Exercises { exercises, .. }, Some(i)) => {
+                exercise_preview(exercises[i].exercise.dir())
+            }
+            (Screen::Types { course, types }, Some(i)) => {
+                let ex_type = &self.courses[*course].config().exercise_types[&types[i]];
+                vec![
+                    Line::from(format!("template: {}", ex_type.template)),
+                    Line::from(format!("naming scheme: {}", ex_type.naming_scheme)),
+                ]
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::bordered())
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+
+    fn course_preview(&self, item: &Course) -> Vec<Line<'static>> {
+        let course = &item.config().course;
+        let mut lines =
+            vec![Line::from(course.name.clone().unwrap_or_else(|| course.code.clone())).bold()];
+        let mut field = |key: &str, value: String| {
+            lines.push(Line::from(vec![
+                Span::from(format!("{}: ", key)).dim(),
+                Span::from(value),
+            ]));
+        };
+        field("code", course.code.clone());
+        if !course.aliases.is_empty() {
+            field("aliases", course.aliases.join(", "));
+        }
+        if let Some(term) = &course.term {
+            field("term", term.clone());
+        }
+        field("exercises", item.exercises().len().to_string());
+        if let Some(last) = self.state.last_exercises.get(&course.code) {
+            field("last", last.clone());
+        }
+        if !item.config().exercise_types.is_empty() {
+            let mut types: Vec<&str> = item
+                .config()
+                .exercise_types
+                .keys()
+                .map(String::as_str)
+                .collect();
+            types.sort();
+            field("types", types.join(", "));
+        }
+        field("path", item.dir().display().to_string());
+        lines
+    }
+}
+
+/// Courses in recent-first order: the last course and `recent_courses`,
+/// then the rest by code.
+fn load_courses(workspace: &Workspace, all: bool, state: &StudyState) -> Vec<Course> {
+    let mut courses = workspace.courses(all);
+    courses.sort_by_cached_key(|course| {
+        let code = course.code();
+        let recent = (state.last_course.iter())
+            .chain(&state.recent_courses)
+            .position(|c| c == code);
+        (recent.unwrap_or(usize::MAX), code.to_string())
+    });
+    courses
+}
+
+/// Exercises of a course: the last studied one first, then by modification time.
+fn load_exercises(course: &Course, state: &StudyState) -> Vec<ExerciseItem> {
+    let last = state.last_exercises.get(course.code()).map(String::as_str);
+    let mut exercises: Vec<(SystemTime, ExerciseItem)> = course
+        .exercises()
+        .into_iter()
+        .map(|exercise| {
+            let modified = fs::metadata(exercise.dir())
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let title = exercise
+                .meta()
+                .ok()
+                .and_then(|meta| meta.exercise.title().map(String::from));
+            (modified, ExerciseItem { exercise, title })
+        })
+        .collect();
+    exercises.sort_by(|(a_time, a), (b_time, b)| {
+        (Some(b.exercise.name()) == last)
+            .cmp(&(Some(a.exercise.name()) == last))
+            .then(b_time.cmp(a_time))
+    });
+    exercises.into_iter().map(|(_, item)| item).collect()
+}
+
+/// `meta.toml` followed by the exercise's files.
+fn exercise_preview(dir: &Path) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line> = fs::read_to_string(dir.join("meta.toml"))
+        .map(|meta| meta.lines().map(|l| Line::from(l.to_string())).collect())
+        .unwrap_or_default();
+
+    let mut files: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            if e.file_type().is_ok_and(|t| t.is_dir()) {
+                format!("{}/", name)
+            } else {
+                name
+            }
+        })
+        .collect();
+    files.sort();
+
+    if !lines.is_empty() {
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from("Files").bold());
+    lines.extend(files.into_iter().map(|f| Line::from(format!("  {}", f))));
+    lines
+}
+
+/// Open `dir` in the desktop's file manager.
+fn open_dir(dir: &Path) -> io::Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(opener)
+        .arg(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    println!("Opened {}", dir.display());
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use crate::cli::SchemaFile;
+use std::io;
+use study::schema::{self, json_schema};
+
+/// Print a JSON Schema for `course.toml`, `config.toml` or `meta.toml`.
+pub fn run(file: SchemaFile) -> io::Result<()> {
+    let schema = match file {
+        SchemaFile::Course => json_schema(&schema::COURSE, "study course.toml"),
+        SchemaFile::Global => json_schema(&schema::GLOBAL, "study config.toml"),
+        SchemaFile::Meta => json_schema(&schema::META, "study meta.toml"),
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema).map_err(io::Error::other)?
+    );
+    Ok(())
+}
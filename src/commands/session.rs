@@ -0,0 +1,118 @@
+use std::io;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use study::ProcessGroup;
+
+/// Child processes started by one `study` invocation.
+///
+/// Background commands each get their own process group so the whole tree
+/// under `sh -c` can be signalled at once, both when Ctrl-C is forwarded and
+/// from a later `study stop`.
+pub struct Session {
+    background: Vec<(String, Child)>,
+    groups: Arc<Mutex<Vec<ProcessGroup>>>,
+    interrupted: Arc<AtomicBool>,
+    failures: usize,
+}
+
+impl Session {
+    /// Create a session and install a Ctrl-C handler that forwards SIGINT to
+    /// every background process group.
+    pub fn new() -> io::Result<Self> {
+        let groups = Arc::new(Mutex::new(Vec::<ProcessGroup>::new()));
+        let interrupted = Arc::new(AtomicBool::new(false));
+
+        let handler_groups = Arc::clone(&groups);
+        let handler_interrupted = Arc::clone(&interrupted);
+        ctrlc::set_handler(move || {
+            handler_interrupted.store(true, Ordering::SeqCst);
+            if let Ok(groups) = handler_groups.lock() {
+                for group in groups.iter() {
+                    group.signal(libc::SIGINT);
+                }
+            }
+        })
+        .map_err(io::Error::other)?;
+
+        Ok(Self {
+            background: Vec::new(),
+            groups,
+            interrupted,
+            failures: 0,
+        })
+    }
+
+    /// Run `command`, either waiting for it to exit or leaving it in the background.
+    pub fn run(&mut self, label: &str, mut command: Command, wait: bool) -> io::Result<()> {
+        if wait {
+            let status = command.status()?;
+            self.report(label, status);
+            return Ok(());
+        }
+
+        let child = command.process_group(0).spawn()?;
+        if let Ok(mut groups) = self.groups.lock() {
+            groups.push(ProcessGroup::new(child.id()));
+        }
+        self.background.push((label.to_string(), child));
+        Ok(())
+    }
+
+    /// Start `command` outside the session: it is not waited for, not sent
+    /// Ctrl-C and not recorded for `study stop`.
+    pub fn detach(&mut self, mut command: Command) -> io::Result<()> {
+        command
+            .process_group(0)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map(|_| ())
+    }
+
+    /// Wait for all background commands to exit, reporting failures.
+    pub fn wait(&mut self) {
+        let mut statuses = Vec::new();
+        for (label, child) in &mut self.background {
+            match child.wait() {
+                Ok(status) => statuses.push((label.clone(), status)),
+                Err(e) => eprintln!("  Failed to wait for '{}': {}", label, e),
+            }
+        }
+        for (label, status) in statuses {
+            self.report(&label, status);
+        }
+    }
+
+    /// Process groups of the background commands.
+    pub fn pids(&self) -> Vec<ProcessGroup> {
+        self.groups
+            .lock()
+            .map(|groups| groups.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    /// Number of commands that exited unsuccessfully.
+    pub fn failures(&self) -> usize {
+        self.failures
+    }
+
+    fn report(&mut self, label: &str, status: ExitStatus) {
+        // Commands stopped by a forwarded Ctrl-C are not failures.
+        if status.success() || (self.interrupted() && status.signal() == Some(libc::SIGINT)) {
+            return;
+        }
+        self.failures += 1;
+        match (status.code(), status.signal()) {
+            (Some(code), _) => eprintln!("  '{}' exited with status {}", label, code),
+            (None, Some(signal)) => eprintln!("  '{}' was killed by signal {}", label, signal),
+            (None, None) => eprintln!("  '{}' failed", label),
+        }
+    }
+}
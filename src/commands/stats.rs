@@ -0,0 +1,175 @@
+use super::{course_code, print_structured};
+use crate::cli::OutputFormat;
+use chrono::{Datelike, TimeDelta};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use study::Workspace;
+use study::history::{format_duration, load_history};
+
+#[derive(Serialize)]
+struct Stats {
+    seconds: i64,
+    sessions: usize,
+    courses: Vec<CourseStats>,
+    weeks: Vec<WeekStats>,
+}
+
+#[derive(Serialize)]
+struct CourseStats {
+    course: String,
+    seconds: i64,
+    sessions: usize,
+    exercises: Vec<ExerciseStats>,
+}
+
+#[derive(Serialize)]
+struct ExerciseStats {
+    exercise: String,
+    seconds: i64,
+    sessions: usize,
+}
+
+#[derive(Serialize)]
+struct WeekStats {
+    /// ISO week, e.g. `2025-W42`.
+    week: String,
+    seconds: i64,
+    sessions: usize,
+}
+
+/// Time and session count.
+#[derive(Default)]
+struct Total {
+    seconds: i64,
+    sessions: usize,
+}
+
+impl Total {
+    fn add(&mut self, seconds: i64) {
+        self.seconds += seconds;
+        self.sessions += 1;
+    }
+}
+
+/// Label for sessions on a course without an exercise.
+const COURSE_ONLY: &str = "(course)";
+
+pub fn run(workspace: &Workspace, course: Option<&str>, format: OutputFormat) -> io::Result<()> {
+    let code = course
+        .map(|input| course_code(workspace, input))
+        .transpose()?;
+
+    let mut total = Total::default();
+    let mut courses: BTreeMap<String, (Total, BTreeMap<String, Total>)> = BTreeMap::new();
+    let mut weeks: BTreeMap<(i32, u32), Total> = BTreeMap::new();
+
+    for record in load_history()? {
+        if code.as_ref().is_some_and(|code| &record.course != code) {
+            continue;
+        }
+        let seconds = record.duration().num_seconds();
+        let week = record.start.iso_week();
+
+        total.add(seconds);
+        weeks
+            .entry((week.year(), week.week()))
+            .or_default()
+            .add(seconds);
+        let (course_total, exercises) = courses.entry(record.course).or_default();
+        course_total.add(seconds);
+        exercises
+            .entry(record.exercise.unwrap_or_else(|| COURSE_ONLY.to_string()))
+            .or_default()
+            .add(seconds);
+    }
+
+    let mut courses: Vec<CourseStats> = courses
+        .into_iter()
+        .map(|(course, (total, exercises))| CourseStats {
+            course,
+            seconds: total.seconds,
+            sessions: total.sessions,
+            exercises: exercises
+                .into_iter()
+                .map(|(exercise, total)| ExerciseStats {
+                    exercise,
+                    seconds: total.seconds,
+                    sessions: total.sessions,
+                })
+                .collect(),
+        })
+        .collect();
+    courses.sort_by_key(|c| std::cmp::Reverse(c.seconds));
+
+    let stats = Stats {
+        seconds: total.seconds,
+        sessions: total.sessions,
+        courses,
+        weeks: weeks
+            .into_iter()
+            .map(|((year, week), total)| WeekStats {
+                week: format!("{}-W{:02}", year, week),
+                seconds: total.seconds,
+                sessions: total.sessions,
+            })
+            .collect(),
+    };
+
+    match format {
+        OutputFormat::Text => print_stats(&stats),
+        _ => print_structured(&stats, format)?,
+    }
+    Ok(())
+}
+
+fn print_stats(stats: &Stats) {
+    if stats.sessions == 0 {
+        println!("No study sessions recorded.");
+        return;
+    }
+
+    let time = |seconds: i64| format_duration(TimeDelta::seconds(seconds));
+    println!(
+        "Total: {} in {} session(s)",
+        time(stats.seconds),
+        stats.sessions
+    );
+
+    println!("\nCourses");
+    let width = stats
+        .courses
+        .iter()
+        .flat_map(|c| {
+            std::iter::once(c.course.len()).chain(c.exercises.iter().map(|e| e.exercise.len() + 2))
+        })
+        .max()
+        .unwrap_or(0);
+    for course in &stats.courses {
+        println!(
+            "  {:<width$}  {:>8}  {} session(s)",
+            course.course,
+            time(course.seconds),
+            course.sessions
+        );
+        for exercise in &course.exercises {
+            println!(
+                "    {:<w$}  {:>8}  {} session(s)",
+                exercise.exercise,
+                time(exercise.seconds),
+                exercise.sessions,
+                w = width - 2
+            );
+        }
+    }
+
+    println!("\nWeeks");
+    for week in &stats.weeks {
+        println!(
+            "  {}  {:>8}  {} session(s)",
+            week.week,
+            time(week.seconds),
+            week.sessions
+        );
+    }
+}
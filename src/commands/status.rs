@@ -0,0 +1,103 @@
+use super::relative_due;
+use chrono::NaiveDate;
+use std::io;
+use study::{Exercise, ExerciseStatus, Workspace};
+use toml_edit::{Array, value};
+
+/// Changes requested on the command line.
+pub struct Update {
+    pub status: Option<ExerciseStatus>,
+    pub due: Option<NaiveDate>,
+    pub points: Option<f64>,
+    pub grade: Option<String>,
+    pub tags: Vec<String>,
+    pub untags: Vec<String>,
+}
+
+impl Update {
+    fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.due.is_none()
+            && self.points.is_none()
+            && self.grade.is_none()
+            && self.tags.is_empty()
+            && self.untags.is_empty()
+    }
+}
+
+pub fn run(
+    workspace: &Workspace,
+    exercise: &str,
+    course: Option<&str>,
+    update: Update,
+) -> io::Result<()> {
+    let exercise = workspace.course_or_cwd(course)?.find_exercise(exercise)?;
+
+    if !update.is_empty() {
+        // Validate before editing so a broken meta.toml is reported, not patched.
+        exercise.meta()?;
+        exercise.edit_meta(|doc| apply(doc, update))?;
+    }
+    print_status(&exercise)
+}
+
+fn apply(doc: &mut toml_edit::DocumentMut, update: Update) {
+    let exercise = &mut doc["exercise"];
+    if let Some(status) = update.status {
+        exercise["status"] = value(status.to_string());
+    }
+    if let Some(due) = update.due {
+        exercise["due"] = value(due.to_string());
+    }
+    if let Some(points) = update.points {
+        exercise["points"] = value(points);
+    }
+    if let Some(grade) = update.grade {
+        exercise["grade"] = value(grade);
+    }
+
+    if update.tags.is_empty() && update.untags.is_empty() {
+        return;
+    }
+    let mut tags: Vec<String> = exercise
+        .get("tags")
+        .and_then(|tags| tags.as_array())
+        .map(|tags| {
+            tags.iter()
+                .filter_map(|t| t.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+    for tag in update.tags {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags.retain(|tag| !update.untags.contains(tag));
+    exercise["tags"] = value(tags.into_iter().collect::<Array>());
+}
+
+fn print_status(exercise: &Exercise) -> io::Result<()> {
+    let exercise = exercise.meta()?.exercise;
+    match exercise.title() {
+        Some(title) => println!("{}  {}", exercise.name, title),
+        None => println!("{}", exercise.name),
+    }
+    println!("  status: {}", exercise.status);
+    if let Some(due) = exercise.due {
+        println!("  due:    {} ({})", due, relative_due(due));
+    }
+    if let Some(points) = exercise.points {
+        println!("  points: {}", points);
+    }
+    if let Some(grade) = &exercise.grade {
+        println!("  grade:  {}", grade);
+    }
+    if !exercise.tags.is_empty() {
+        println!("  tags:   {}", exercise.tags.join(", "));
+    }
+    if let Some(submitted) = exercise.extra.get("submitted").and_then(|s| s.as_str()) {
+        println!("  submitted: {}", submitted);
+    }
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use std::io;
+use study::Workspace;
+
+pub fn run(workspace: &Workspace) -> io::Result<()> {
+    match workspace.stop_session()? {
+        None => println!("No running study session."),
+        Some(stopped) => println!("Stopped {} process group(s).", stopped),
+    }
+    Ok(())
+}
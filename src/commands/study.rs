@@ -1,17 +1,23 @@
-use crate::config::{
-    GlobalConfig, find_course_dir, find_latest_exercise, load_course_config, load_state,
-    save_state,
-};
+use super::session::Session;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use study::config::{
+    CommandSpec, CourseInfo, ExerciseMeta, expand_tilde, find_due_exercise, find_latest_exercise,
+    load_meta, load_state, merge_study_commands, save_state,
+};
+use study::history::{self, SessionRecord};
+use study::template::{TemplateVars, expand, insert_flattened};
+use study::{Course, Exercise, Workspace};
 
 pub fn run(
-    global: &GlobalConfig,
+    workspace: &Workspace,
     course_name: Option<&str>,
     exercise_name: Option<&str>,
+    wait: bool,
+    all: bool,
 ) -> io::Result<()> {
-    let mut state = load_state();
-
+    let state = load_state();
     let course_input = course_name
         .or(state.last_course.as_deref())
         .ok_or_else(|| {
@@ -19,35 +25,43 @@ pub fn run(
                 io::ErrorKind::NotFound,
                 "No course specified and no previous session found.",
             )
-        })?
-        .to_string();
-
-    let course_dir = find_course_dir(global, &course_input).ok_or_else(|| {
-        io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("Course not found: {}", course_input),
-        )
-    })?;
-
-    let config = load_course_config(&course_dir)?;
-    let course_code = &config.course.code;
-    let display_name = config
-        .course
-        .name
-        .as_deref()
-        .unwrap_or(course_code)
-        .to_string();
+        })?;
 
+    let course = workspace.find_course(course_input, all)?;
     let exercise = exercise_name
-        .map(String::from)
-        .or_else(|| state.last_exercises.get(course_code).cloned())
-        .or_else(|| find_latest_exercise(&course_dir));
+        .map(|input| course.find_exercise(input))
+        .transpose()?;
+    run_in(workspace, &course, exercise.as_ref(), wait)
+}
+
+/// Study `course`, in `exercise` or else the one to study next.
+pub fn run_in(
+    workspace: &Workspace,
+    course: &Course,
+    exercise: Option<&Exercise>,
+    wait: bool,
+) -> io::Result<()> {
+    let global = workspace.global();
+    let mut state = load_state();
+
+    let course_dir = course.dir().to_path_buf();
+    let config = course.config();
+    let course_code = course.code();
+    let display_name = course.display_name();
+
+    let exercise = match exercise {
+        Some(exercise) => Some(exercise.name().to_string()),
+        // Prefer the most urgent exercise, then the one studied last.
+        None => find_due_exercise(&course_dir)
+            .or_else(|| state.last_exercises.get(course_code).cloned())
+            .or_else(|| find_latest_exercise(&course_dir)),
+    };
 
     // If no exercise was resolved and the course supports exercises, prompt to create one
     let exercise = match exercise {
         Some(ex) => Some(ex),
         None if !config.exercise_types.is_empty() => {
-            prompt_create_exercise(global, &course_input, &display_name)?;
+            prompt_create_exercise(workspace, course)?;
             state = load_state();
             state
                 .last_exercises
@@ -64,9 +78,22 @@ pub fn run(
         .filter(|dir| dir.is_dir())
         .unwrap_or_else(|| course_dir.clone());
 
-    if config.study.commands.is_empty() {
-        println!("No study commands configured for '{}'.", course_input);
-        return Ok(());
+    let meta = load_meta(&work_dir).ok();
+    let exercise_type = meta.as_ref().map(|m| m.exercise.exercise_type.clone());
+    let type_study = exercise_type
+        .as_deref()
+        .and_then(|t| config.exercise_types.get(t))
+        .map(|t| &t.study);
+    let commands =
+        merge_study_commands([&global.study, &config.study].into_iter().chain(type_study));
+
+    let studied_exercise = exercise.as_deref().filter(|_| work_dir != course_dir);
+    let mut record = SessionRecord::new(course_code, studied_exercise);
+
+    if commands.is_empty() {
+        println!("No study commands configured for '{}'.", display_name);
+        record.end = Some(record.start);
+        return history::append(&record);
     }
 
     match exercise.as_deref() {
@@ -81,35 +108,195 @@ pub fn run(
         }
     }
 
-    for cmd in &config.study.commands {
-        println!("  Running: {}", cmd);
-        if let Err(e) = Command::new("sh")
-            .arg("-c")
-            .arg(cmd)
-            .current_dir(&work_dir)
-            .spawn()
-        {
-            eprintln!("  Failed to run '{}': {}", cmd, e);
+    let vars = command_vars(
+        &config.course,
+        &course_dir,
+        &work_dir,
+        studied_exercise,
+        meta.as_ref(),
+    );
+
+    let mut session = Session::new()?;
+    for spec in &commands {
+        let spec = expand_spec(spec.spec(), &vars);
+        if session.interrupted() {
+            break;
+        }
+        if !condition_holds(&spec, &work_dir, exercise_type.as_deref()) {
+            continue;
+        }
+        let result = command_line(&spec.run, &vars).and_then(|line| {
+            println!("  Running: {}", line);
+            let command = build_command(&spec, &work_dir, &vars)?;
+            if spec.detach {
+                session.detach(command)
+            } else {
+                session.run(&line, command, spec.wait)
+            }
+        });
+        if let Err(e) = result {
+            eprintln!("  Failed to run '{}': {}", spec.run, e);
         }
     }
 
-    state.last_course = Some(course_code.clone());
-    if let Some(ex) = exercise {
-        state.last_exercises.insert(course_code.clone(), ex);
+    // The session lasts as long as its background commands.
+    record.pids = session.pids();
+    if record.pids.is_empty() {
+        record.end = Some(chrono::Local::now());
     }
+    history::append(&record)?;
+
+    state.record(course_code, exercise.clone());
+    state.session_pids = session.pids();
+    state.session_id = Some(record.id);
     save_state(&state)?;
 
-    Ok(())
+    if wait {
+        session.wait();
+        history::finish(record.id)?;
+
+        // Another session may have started in the meantime; only forget our own PIDs.
+        let mut state = load_state();
+        if state.session_pids == session.pids() {
+            state.session_pids.clear();
+            state.session_id = None;
+            save_state(&state)?;
+        }
+    } else if !record.pids.is_empty() {
+        history::spawn_watcher(record.id)?;
+    }
+
+    match session.failures() {
+        0 => Ok(()),
+        n => Err(io::Error::other(format!("{} study command(s) failed", n))),
+    }
 }
 
-fn prompt_create_exercise(
-    global: &GlobalConfig,
-    course_input: &str,
-    display_name: &str,
-) -> io::Result<()> {
+/// Resolve a command path, with its placeholders already expanded: relative
+/// paths are relative to the exercise. `{course_dir}/...` reaches the course.
+fn resolve_path(path: &str, work_dir: &Path) -> PathBuf {
+    work_dir.join(expand_tilde(path))
+}
+
+pub(super) fn condition_holds(
+    spec: &CommandSpec,
+    work_dir: &Path,
+    exercise_type: Option<&str>,
+) -> bool {
+    let Some(when) = &spec.when else {
+        return true;
+    };
+    let exists = when
+        .exists
+        .as_deref()
+        .is_none_or(|path| resolve_path(path, work_dir).exists());
+    let type_matches =
+        when.types.is_empty() || exercise_type.is_some_and(|t| when.types.iter().any(|w| w == t));
+    exists && type_matches
+}
+
+pub(super) fn build_command(
+    spec: &CommandSpec,
+    work_dir: &Path,
+    vars: &TemplateVars,
+) -> io::Result<Command> {
+    let mut command = if spec.shell {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line(&spec.run, vars)?);
+        command
+    } else {
+        let argv: Vec<String> = shlex::split(&spec.run)
+            .filter(|argv| !argv.is_empty())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid command line"))?
+            .iter()
+            .map(|arg| expand(arg, vars))
+            .collect();
+        let mut command = Command::new(&argv[0]);
+        command.args(&argv[1..]);
+        command
+    };
+
+    let cwd = spec
+        .cwd
+        .as_deref()
+        .map_or_else(|| work_dir.to_path_buf(), |cwd| resolve_path(cwd, work_dir));
+    command
+        .current_dir(cwd)
+        .envs(vars.iter().map(|(key, value)| (env_name(key), value)))
+        .envs(&spec.env);
+    Ok(command)
+}
+
+/// Variables for `{name}` placeholders in study commands.
+///
+/// Includes every field of the exercise's `meta.toml` (e.g. `{exercise.type}`),
+/// with the course identity taken from `course.toml`.
+pub(super) fn command_vars(
+    course: &CourseInfo,
+    course_dir: &Path,
+    work_dir: &Path,
+    exercise: Option<&str>,
+    meta: Option<&ExerciseMeta>,
+) -> TemplateVars {
+    let mut vars = TemplateVars::new();
+    if let Some(toml::Value::Table(table)) = meta.and_then(|m| toml::Value::try_from(m).ok()) {
+        for (key, value) in &table {
+            insert_flattened(&mut vars, key, value);
+        }
+    }
+    vars.insert("course_dir".into(), course_dir.display().to_string());
+    vars.insert("exercise_dir".into(), work_dir.display().to_string());
+    if let Some(exercise) = exercise {
+        vars.insert("exercise".into(), exercise.into());
+    }
+    vars.insert("course.code".into(), course.code.clone());
+    vars.insert(
+        "course.name".into(),
+        course.name.clone().unwrap_or_else(|| course.code.clone()),
+    );
+    vars
+}
+
+/// `run` with its placeholders expanded to shell-quoted values, so that a
+/// path with spaces or quotes stays one word.
+pub(super) fn command_line(run: &str, vars: &TemplateVars) -> io::Result<String> {
+    let quoted = vars
+        .iter()
+        .map(|(key, value)| {
+            let value = shlex::try_quote(value).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{{{}}} contains a NUL byte", key),
+                )
+            })?;
+            Ok((key.clone(), value.into_owned()))
+        })
+        .collect::<io::Result<TemplateVars>>()?;
+    Ok(expand(run, &quoted))
+}
+
+/// Expand placeholders in everything but `run`, which `build_command`
+/// expands once it knows how the line is split into words.
+pub(super) fn expand_spec(mut spec: CommandSpec, vars: &TemplateVars) -> CommandSpec {
+    spec.cwd = spec.cwd.map(|cwd| expand(&cwd, vars));
+    for value in spec.env.values_mut() {
+        *value = expand(value, vars);
+    }
+    if let Some(when) = &mut spec.when {
+        when.exists = when.exists.as_deref().map(|path| expand(path, vars));
+    }
+    spec
+}
+
+/// Environment variable for a placeholder: `course.code` → `STUDY_COURSE_CODE`.
+fn env_name(key: &str) -> String {
+    format!("STUDY_{}", key.to_uppercase().replace(['.', '-'], "_"))
+}
+
+fn prompt_create_exercise(workspace: &Workspace, course: &Course) -> io::Result<()> {
     print!(
         "No exercises found for '{}'. Create one? [Y/n] ",
-        display_name
+        course.display_name()
     );
     io::stdout().flush()?;
 
@@ -121,5 +308,5 @@ fn prompt_create_exercise(
         return Ok(());
     }
 
-    crate::commands::init_exercise::run(global, None, None, Some(course_input))
+    super::init_exercise::run_in(workspace, course, None, None, None)
 }
@@ -0,0 +1,76 @@
+use std::io;
+use study::template::TemplateRegistry;
+use study::{Error, Workspace};
+
+pub fn list(workspace: &Workspace, course: Option<&str>) -> io::Result<()> {
+    let registry = registry(workspace, course)?;
+    let templates = registry.list()?;
+
+    let name_width = templates.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    for template in &templates {
+        let extends = template
+            .manifest
+            .extends
+            .as_deref()
+            .map(|parent| format!(" (extends {})", parent))
+            .unwrap_or_default();
+        println!(
+            "{:<name_width$}  {}{}",
+            template.name,
+            template.origin(),
+            extends
+        );
+    }
+
+    Ok(())
+}
+
+pub fn show(workspace: &Workspace, name: &str, course: Option<&str>) -> io::Result<()> {
+    let registry = registry(workspace, course)?;
+    let chain = registry.chain(name)?;
+
+    for (depth, template) in chain.iter().rev().enumerate() {
+        let label = if depth == 0 { "Template" } else { "Extends" };
+        println!("{}: {} ({})", label, template.name, template.origin());
+        if let Some(description) = &template.manifest.description {
+            println!("  {}", description);
+        }
+    }
+
+    println!("Files:");
+    let mut files: Vec<_> = chain
+        .iter()
+        .flat_map(|t| t.files().into_iter().map(move |f| (f, t)))
+        .collect();
+    // Later templates in the chain overlay earlier ones.
+    files.reverse();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files.dedup_by(|a, b| a.0 == b.0);
+    for (file, template) in files {
+        println!("  {}  ({})", file.display(), template.origin());
+    }
+
+    println!("Search path:");
+    for dir in registry.search_path() {
+        println!("  {}", dir.display());
+    }
+
+    Ok(())
+}
+
+/// Build the registry for `--course`, the course containing the current
+/// directory, or just the global and built-in templates.
+fn registry(workspace: &Workspace, course: Option<&str>) -> io::Result<TemplateRegistry> {
+    let course = match course {
+        Some(name) => Some(workspace.find_course(name, false)?),
+        None => match std::env::current_dir().map(|cwd| workspace.course_at(&cwd)) {
+            Ok(Ok(course)) => Some(course),
+            Ok(Err(Error::NotInCourse(_))) | Err(_) => None,
+            Ok(Err(e)) => return Err(e.into()),
+        },
+    };
+    let course = course
+        .as_ref()
+        .map(|course| (course.dir(), course.config()));
+    Ok(TemplateRegistry::new(workspace.global(), course))
+}
@@ -1,16 +1,23 @@
-use crate::config::{course_dirs, load_course_config, load_global_config};
-use std::path::Path;
+use clap_complete::engine::CompletionCandidate;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use study::config::{
+    course_dirs, exercise_dirs, find_course_root, load_course_config, load_global_config,
+    unique_course_dir,
+};
 
-/// Scan `<courses_dir>/<faculty>/<course>/course.toml` to collect course codes and names.
+/// Scan all course.toml files to collect course codes, names and aliases.
 pub fn list_courses() -> Vec<String> {
     let Ok(global) = load_global_config() else {
         return Vec::new();
     };
 
-    course_dirs(&global)
+    course_dirs(&global, false)
         .filter_map(|dir| load_course_config(&dir).ok())
         .flat_map(|config| {
-            std::iter::once(config.course.code).chain(config.course.name)
+            std::iter::once(config.course.code)
+                .chain(config.course.name)
+                .chain(config.course.aliases)
         })
         .collect()
 }
@@ -21,3 +28,89 @@ pub fn list_exercise_types(course_dir: &Path) -> Vec<String> {
         .map(|config| config.exercise_types.into_keys().collect())
         .unwrap_or_default()
 }
+
+/// List exercise directory names in a course.
+pub fn list_exercises(course_dir: &Path) -> Vec<String> {
+    exercise_dirs(course_dir)
+        .filter_map(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect()
+}
+
+/// Completer for course arguments: course codes, names and aliases.
+pub fn complete_course(current: &OsStr) -> Vec<CompletionCandidate> {
+    candidates(list_courses(), current)
+}
+
+/// Completer for exercise arguments: directories inside the course on the
+/// command line, or of the course containing the current directory.
+pub fn complete_exercise(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(course_dir) = command_line_or_cwd_course() else {
+        return Vec::new();
+    };
+    candidates(list_exercises(&course_dir), current)
+}
+
+/// Completer for `--type`: exercise types of the `--course` course, or of the
+/// course containing the current directory.
+pub fn complete_exercise_type(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(course_dir) = command_line_or_cwd_course() else {
+        return Vec::new();
+    };
+    candidates(list_exercise_types(&course_dir), current)
+}
+
+fn command_line_or_cwd_course() -> Option<PathBuf> {
+    match course_on_command_line() {
+        Some(course) => lookup_course(&course),
+        None => std::env::current_dir()
+            .ok()
+            .and_then(|cwd| find_course_root(&cwd)),
+    }
+}
+
+fn candidates(values: Vec<String>, current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let mut values: Vec<String> = values
+        .into_iter()
+        .filter(|v| v.starts_with(current.as_ref()))
+        .collect();
+    values.sort();
+    values.dedup();
+    values.into_iter().map(CompletionCandidate::new).collect()
+}
+
+fn lookup_course(input: &str) -> Option<PathBuf> {
+    let global = load_global_config().ok()?;
+    unique_course_dir(&global, input, false)
+}
+
+/// Find the course named on the command line being completed.
+///
+/// During completion the shell invokes `study -- <words...>`, so the words are
+/// everything after the first `--`. The course is the value of `--course`/`-c`,
+/// the positional after the `study` subcommand, or the first positional of the
+/// top-level shorthand. The last word is the one being completed and is ignored.
+fn course_on_command_line() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let start = args.iter().position(|a| a == "--")? + 1;
+    let words = args.get(start + 1..args.len().saturating_sub(1))?;
+
+    let mut positionals = Vec::new();
+    let mut iter = words.iter();
+    while let Some(word) = iter.next() {
+        match word.as_str() {
+            "--course" | "-c" => return iter.next().cloned(),
+            w if w.starts_with("--course=") => return Some(w["--course=".len()..].to_string()),
+            w if w.starts_with('-') => {}
+            w => positionals.push(w),
+        }
+    }
+
+    match positionals.as_slice() {
+        ["init", ..] | ["completions", ..] | ["status", ..] | ["pack", ..] => None,
+        ["study", course, ..] => Some(course.to_string()),
+        ["study"] => None,
+        [course, ..] => Some(course.to_string()),
+        [] => None,
+    }
+}
@@ -1,28 +1,109 @@
-use serde::{Deserialize, Serialize};
+use crate::ProcessGroup;
+use crate::course_code::{self, CodeRule, ParsedCode};
+use crate::error::{Error, Result};
+use crate::layout::CourseLayout;
+use crate::matching::{self, Candidate, SelectError};
+use crate::naming::NamingScheme;
+use crate::schema::{self, Shape, UnknownKey};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, de};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::{fs, io};
+use std::{fmt, fs, io};
 
 const COURSE_CONFIG_FILENAME: &str = "course.toml";
 const STATE_FILENAME: &str = "state.toml";
+const META_FILENAME: &str = "meta.toml";
+const PRESETS_DIRNAME: &str = "presets";
+
+/// The `version` written to `config.toml` and `course.toml`.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Changes from one config version to the next: entry `n` turns version `n`
+/// into `n + 1`. Version 0 is any file from before `version` existed; it
+/// differs from version 1 only in lacking the key.
+const MIGRATIONS: &[fn(&mut toml_edit::DocumentMut)] = &[|_| {}];
+
+/// Built-in course presets, used unless a preset file of the same name exists.
+const BUILTIN_PRESETS: &[(&str, &str)] = &[
+    (
+        "latex-problem-sets",
+        r#"
+description = "Problem sets written in LaTeX"
+
+[exercise_types.problem-set]
+template = "latex"
+naming_scheme = "ps{:02}"
+
+[exercise_types.problem-set.study]
+commands = [{ run = "latexmk -pdf -pvc main.tex", when = { exists = "main.tex" } }]
+"#,
+    ),
+    (
+        "programming-labs",
+        r#"
+description = "Programming labs in Python"
+
+[exercise_types.lab]
+template = "python"
+naming_scheme = "lab{:02}"
+"#,
+    ),
+];
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GlobalConfig {
+    /// Format version; older files are migrated when loaded.
+    #[serde(default)]
+    pub version: u32,
     pub courses_dir: String,
     pub default_template_dir: String,
+    /// Directory layout of courses under `courses_dir`.
+    #[serde(default)]
+    pub layout: CourseLayout,
+    /// Ordered rules for splitting course codes into faculty and number.
+    #[serde(default = "course_code::default_rules")]
+    pub code_rules: Vec<CodeRule>,
+    /// Find courses by searching for `course.toml` up to this many levels
+    /// below `courses_dir` instead of following `layout`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discovery_depth: Option<usize>,
+    /// Term of the courses currently being taken (e.g. `2025-autumn`). Courses
+    /// with a different `term` are hidden unless `--all` is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_term: Option<String>,
+    /// Where `study archive` moves courses. Without it, courses are marked
+    /// `archived = true` in place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_dir: Option<String>,
+    /// Default study commands inherited by every course.
+    #[serde(default)]
+    pub study: StudyConfig,
 }
 
 impl Default for GlobalConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             courses_dir: "~/courses".to_string(),
             default_template_dir: "~/.config/study/templates".to_string(),
+            layout: CourseLayout::default(),
+            code_rules: course_code::default_rules(),
+            discovery_depth: None,
+            active_term: None,
+            archive_dir: None,
+            study: StudyConfig::default(),
         }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CourseConfig {
+    /// Format version; older files are migrated when loaded.
+    #[serde(default)]
+    pub version: u32,
     pub course: CourseInfo,
     #[serde(default)]
     pub template_dir: Option<String>,
@@ -37,20 +118,301 @@ pub struct CourseInfo {
     pub code: String,
     #[serde(default)]
     pub name: Option<String>,
+    /// Other names the course can be looked up by (e.g. `linalg`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub term: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub archived: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExerciseType {
     pub template: String,
-    pub naming_scheme: String,
-    #[serde(default)]
+    pub naming_scheme: NamingScheme,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub meta: HashMap<String, toml::Value>,
+    /// Study commands for exercises of this type, run after the course's.
+    #[serde(default, skip_serializing_if = "StudyConfig::is_empty")]
+    pub study: StudyConfig,
+    /// How `study pack` packages exercises of this type for submission.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submit: Option<SubmitConfig>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// Submission packaging for an exercise type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitConfig {
+    /// Globs of files to pack, relative to the exercise directory. `*` does
+    /// not cross directories; use `**` for that.
+    #[serde(default = "default_submit_include")]
+    pub include: Vec<String>,
+    /// Globs of files to leave out. Defaults to `meta.toml` and hidden files.
+    #[serde(default = "default_submit_exclude")]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub format: ArchiveFormat,
+    /// Archive path relative to the exercise directory, with `{name}`
+    /// placeholders as in study commands plus `{date}`. The extension is added
+    /// if missing. Files matching it are never packed, so earlier archives stay
+    /// out of new ones.
+    #[serde(default = "default_submit_output")]
+    pub output: String,
+    /// Commands run in the exercise directory before packing, e.g. to build a PDF.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub build: Vec<StudyCommand>,
+}
+
+impl Default for SubmitConfig {
+    fn default() -> Self {
+        Self {
+            include: default_submit_include(),
+            exclude: default_submit_exclude(),
+            format: ArchiveFormat::default(),
+            output: default_submit_output(),
+            build: Vec::new(),
+        }
+    }
+}
+
+fn default_submit_include() -> Vec<String> {
+    vec!["**".into()]
+}
+
+fn default_submit_exclude() -> Vec<String> {
+    vec![META_FILENAME.into(), "**/.*".into(), "**/.*/**".into()]
+}
+
+fn default_submit_output() -> String {
+    "{course.code}_{exercise.name}".into()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    #[default]
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StudyConfig {
     #[serde(default)]
-    pub commands: Vec<String>,
+    pub commands: Vec<StudyCommand>,
+    /// Keep the commands inherited from the previous level. Levels are merged
+    /// in the order global config → course → exercise type.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub inherit: bool,
+}
+
+impl StudyConfig {
+    /// True if this level neither adds commands nor drops inherited ones.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.inherit
+    }
+}
+
+impl Default for StudyConfig {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            inherit: true,
+        }
+    }
+}
+
+/// Merge study command levels, most general first.
+///
+/// Each level's commands are appended to the ones before it, unless the level
+/// sets `inherit = false`, in which case the earlier commands are dropped.
+pub fn merge_study_commands<'a>(
+    levels: impl IntoIterator<Item = &'a StudyConfig>,
+) -> Vec<StudyCommand> {
+    levels.into_iter().fold(Vec::new(), |mut commands, level| {
+        if !level.inherit {
+            commands.clear();
+        }
+        commands.extend(level.commands.iter().cloned());
+        commands
+    })
+}
+
+/// A study command: either a plain shell string or a table with options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StudyCommand {
+    Shell(String),
+    Spec(CommandSpec),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandSpec {
+    /// Command line. Each `{name}` placeholder expands to one word, quoted
+    /// as needed; the values are also in `STUDY_*` environment variables.
+    pub run: String,
+    /// Working directory, relative to the exercise directory unless it
+    /// starts with e.g. `{course_dir}`. Defaults to the exercise directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<Condition>,
+    /// Wait for the command to exit before starting the next one.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub wait: bool,
+    /// Start the command outside the session: it is neither waited for nor
+    /// stopped by `study stop`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub detach: bool,
+    /// Run via `sh -c`. When false, `run` is split into words and executed directly.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub shell: bool,
+}
+
+/// Conditions that must all hold for a study command to run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Condition {
+    /// Path that must exist, resolved like `CommandSpec::cwd`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<String>,
+    /// Exercise types (from `meta.toml`) the command applies to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+}
+
+impl StudyCommand {
+    /// The command as a `CommandSpec`, with defaults filled in for the string form.
+    pub fn spec(&self) -> CommandSpec {
+        match self {
+            Self::Shell(run) => CommandSpec {
+                run: run.clone(),
+                cwd: None,
+                env: HashMap::new(),
+                when: None,
+                wait: false,
+                detach: false,
+                shell: true,
+            },
+            Self::Spec(spec) => spec.clone(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// A reusable starting point for `course.toml`, stored as
+/// `<config dir>/study/presets/<name>.toml`.
+#[derive(Debug, Deserialize)]
+pub struct Preset {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub template_dir: Option<String>,
+    #[serde(default)]
+    pub exercise_types: HashMap<String, ExerciseType>,
+    #[serde(default)]
+    pub study: StudyConfig,
+}
+
+/// Contents of an exercise's `meta.toml`, as written by `study init exercise`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExerciseMeta {
+    pub course: CourseInfo,
+    pub exercise: ExerciseInfo,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExerciseInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub exercise_type: String,
+    #[serde(default)]
+    pub status: ExerciseStatus,
+    /// Deadline, written as a TOML date (`due = 2025-10-20`) or a string.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_due",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Custom keys copied from `ExerciseType::meta`.
+    #[serde(flatten)]
+    pub extra: HashMap<String, toml::Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExerciseStatus {
+    #[default]
+    Todo,
+    InProgress,
+    Done,
+    Submitted,
+}
+
+impl ExerciseStatus {
+    /// Done and submitted exercises no longer need work.
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Submitted)
+    }
+}
+
+impl fmt::Display for ExerciseStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Todo => "todo",
+            Self::InProgress => "in-progress",
+            Self::Done => "done",
+            Self::Submitted => "submitted",
+        })
+    }
+}
+
+/// Parse a due date, ignoring any time after the `YYYY-MM-DD` part.
+pub fn parse_due(text: &str) -> Result<NaiveDate, String> {
+    let date = text.get(..10).unwrap_or(text);
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("invalid due date '{}', expected YYYY-MM-DD", text))
+}
+
+fn deserialize_due<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDate>, D::Error> {
+    match Option::<toml::Value>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(toml::Value::String(text)) => parse_due(&text).map(Some),
+        Some(toml::Value::Datetime(datetime)) => parse_due(&datetime.to_string()).map(Some),
+        Some(other) => Err(format!("invalid due date {}, expected YYYY-MM-DD", other)),
+    }
+    .map_err(de::Error::custom)
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -59,6 +421,34 @@ pub struct StudyState {
     pub last_course: Option<String>,
     #[serde(default)]
     pub last_exercises: HashMap<String, String>,
+    /// Course codes, most recently studied first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_courses: Vec<String>,
+    /// Process group IDs of background commands started by the last session.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub session_pids: Vec<ProcessGroup>,
+    /// History record of the last session, ended by `study stop`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<i64>,
+}
+
+impl ExerciseInfo {
+    /// The `title` set by the user or imported from a calendar.
+    pub fn title(&self) -> Option<&str> {
+        self.extra.get("title").and_then(|title| title.as_str())
+    }
+}
+
+impl StudyState {
+    /// Remember `course` (and `exercise`, if any) as the most recently studied.
+    pub fn record(&mut self, course: &str, exercise: Option<String>) {
+        self.last_course = Some(course.to_string());
+        self.recent_courses.retain(|code| code != course);
+        self.recent_courses.insert(0, course.to_string());
+        if let Some(exercise) = exercise {
+            self.last_exercises.insert(course.to_string(), exercise);
+        }
+    }
 }
 
 pub fn state_path() -> PathBuf {
@@ -81,8 +471,7 @@ pub fn save_state(state: &StudyState) -> io::Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    let contents =
-        toml::to_string_pretty(state).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let contents = toml::to_string_pretty(state).map_err(io::Error::other)?;
     fs::write(&path, contents)
 }
 
@@ -95,11 +484,7 @@ pub fn find_latest_exercise(course_dir: &Path) -> Option<String> {
         .flatten()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
-        .filter(|e| {
-            !e.file_name()
-                .to_string_lossy()
-                .starts_with('.')
-        })
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
         .filter_map(|e| {
             e.metadata()
                 .ok()
@@ -110,6 +495,38 @@ pub fn find_latest_exercise(course_dir: &Path) -> Option<String> {
         .map(|(name, _)| name)
 }
 
+/// The unfinished exercise with the nearest deadline from today on.
+pub fn find_due_exercise(course_dir: &Path) -> Option<String> {
+    let today = chrono::Local::now().date_naive();
+    exercise_dirs(course_dir)
+        .filter_map(|dir| {
+            let exercise = load_meta(&dir).ok()?.exercise;
+            if exercise.status.is_finished() {
+                return None;
+            }
+            let due = exercise.due.filter(|&due| due >= today)?;
+            Some((due, dir.file_name()?.to_string_lossy().into_owned()))
+        })
+        .min()
+        .map(|(_, name)| name)
+}
+
+/// Iterate exercise directories in a course (non-hidden subdirectories).
+pub fn exercise_dirs(course_dir: &Path) -> impl Iterator<Item = PathBuf> {
+    subdirs(course_dir)
+}
+
+pub fn load_meta(exercise_dir: &Path) -> Result<ExerciseMeta> {
+    let path = exercise_dir.join(META_FILENAME);
+    parse_toml(&path, &read_file(&path)?)
+}
+
+/// The optional `title` of an exercise, from its `meta.toml`.
+pub fn exercise_title(exercise_dir: &Path) -> Option<String> {
+    let meta = load_meta(exercise_dir).ok()?;
+    meta.exercise.extra.get("title")?.as_str().map(String::from)
+}
+
 pub fn expand_tilde(path: &str) -> PathBuf {
     path.strip_prefix("~/")
         .and_then(|rest| dirs::home_dir().map(|home| home.join(rest)))
@@ -123,13 +540,19 @@ pub fn global_config_path() -> PathBuf {
         .join("config.toml")
 }
 
-pub fn load_global_config() -> io::Result<GlobalConfig> {
+/// Load the global config, or use the defaults if there is none.
+pub fn load_global_config() -> Result<GlobalConfig> {
+    Ok(load_global_config_file()?.map_or_else(GlobalConfig::default, |file| file.config))
+}
+
+/// Load the global config file, if there is one, keeping what is needed to
+/// check it for unknown keys and to save it migrated.
+pub fn load_global_config_file() -> Result<Option<ConfigFile<GlobalConfig>>> {
     let path = global_config_path();
     if !path.exists() {
-        return Ok(GlobalConfig::default());
+        return Ok(None);
     }
-    let contents = fs::read_to_string(&path)?;
-    toml::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    load_versioned(path, &schema::GLOBAL).map(Some)
 }
 
 pub fn save_global_config(config: &GlobalConfig) -> io::Result<()> {
@@ -137,24 +560,219 @@ pub fn save_global_config(config: &GlobalConfig) -> io::Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    let contents =
-        toml::to_string_pretty(config).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let contents = toml::to_string_pretty(config).map_err(io::Error::other)?;
     fs::write(&path, contents)
 }
 
-pub fn load_course_config(course_dir: &Path) -> io::Result<CourseConfig> {
-    let path = course_dir.join(COURSE_CONFIG_FILENAME);
-    let contents = fs::read_to_string(&path)?;
-    toml::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+pub fn presets_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("~/.config"))
+        .join("study")
+        .join(PRESETS_DIRNAME)
+}
+
+/// Load a preset by name from the presets directory, falling back to the built-ins.
+pub fn load_preset(name: &str) -> Result<Preset> {
+    let path = presets_dir().join(format!("{}.toml", name));
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => BUILTIN_PRESETS
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, contents)| contents.to_string())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "Preset not found: {}. Available: {}",
+                        name,
+                        list_presets().join(", ")
+                    ),
+                )
+            })?,
+        Err(e) => return Err(Error::io(path, e)),
+    };
+    parse_toml(&path, &contents)
+}
+
+/// Names of all presets: files in the presets directory plus the built-ins.
+pub fn list_presets() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(presets_dir())
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .chain(BUILTIN_PRESETS.iter().map(|(name, _)| name.to_string()))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+pub fn load_course_config(course_dir: &Path) -> Result<CourseConfig> {
+    Ok(load_course_config_file(course_dir)?.config)
+}
+
+/// Like `load_course_config`, keeping what is needed to check the file for
+/// unknown keys and to save it migrated.
+pub fn load_course_config_file(course_dir: &Path) -> Result<ConfigFile<CourseConfig>> {
+    load_versioned(course_dir.join(COURSE_CONFIG_FILENAME), &schema::COURSE)
+}
+
+/// A loaded `config.toml` or `course.toml`.
+///
+/// Files of an older `version` are migrated in memory only; `save_migrated`
+/// writes the result back.
+#[derive(Debug)]
+pub struct ConfigFile<T> {
+    pub config: T,
+    pub path: PathBuf,
+    /// The version of the file on disk.
+    pub version: u32,
+    /// The contents after migration.
+    contents: String,
+    shape: &'static Shape,
+}
+
+impl<T> ConfigFile<T> {
+    /// Whether the file on disk predates `CONFIG_VERSION`.
+    pub fn is_outdated(&self) -> bool {
+        self.version < CONFIG_VERSION
+    }
+
+    /// Keys that are not part of the config format, such as misspellings.
+    pub fn unknown_keys(&self) -> Vec<UnknownKey> {
+        schema::unknown_keys(&self.contents, self.shape).unwrap_or_default()
+    }
+
+    /// The unknown keys as errors that show the offending lines.
+    pub fn unknown_key_errors(&self) -> Vec<Error> {
+        self.unknown_keys()
+            .iter()
+            .map(|key| key.error(&self.path, &self.contents))
+            .collect()
+    }
+
+    /// Write the migrated file back, keeping its comments and formatting.
+    pub fn save_migrated(&self) -> Result<()> {
+        fs::write(&self.path, &self.contents).map_err(|e| Error::io(&self.path, e))
+    }
+}
+
+/// Load a `config.toml` or `course.toml` and migrate it to `CONFIG_VERSION`.
+fn load_versioned<T: DeserializeOwned>(
+    path: PathBuf,
+    shape: &'static Shape,
+) -> Result<ConfigFile<T>> {
+    let mut contents = read_file(&path)?;
+    let version = config_version(&path, &contents)?;
+    if version < CONFIG_VERSION {
+        contents = migrate(&path, &contents, version)?;
+    }
+    Ok(ConfigFile {
+        config: parse_toml(&path, &contents)?,
+        path,
+        version,
+        contents,
+        shape,
+    })
+}
+
+/// The `version` of a config file, 0 if it has none.
+fn config_version(path: &Path, contents: &str) -> Result<u32> {
+    let document = toml_edit::ImDocument::parse(contents)
+        .map_err(|e| Error::config(path, contents, e.message(), e.span()))?;
+    let Some(item) = document.get("version") else {
+        return Ok(0);
+    };
+    match item.as_integer().map(u32::try_from) {
+        Some(Ok(version)) if version <= CONFIG_VERSION => Ok(version),
+        Some(Ok(version)) => Err(Error::config(
+            path,
+            contents,
+            format!(
+                "config version {} is newer than this study supports ({})",
+                version, CONFIG_VERSION
+            ),
+            item.span(),
+        )
+        .with_hint("upgrade study to use this file")),
+        _ => Err(Error::config(
+            path,
+            contents,
+            format!("invalid config version, expected 0 to {}", CONFIG_VERSION),
+            item.span(),
+        )),
+    }
+}
+
+/// The contents of a config file brought from `version` up to
+/// `CONFIG_VERSION`, keeping its comments and formatting.
+fn migrate(path: &Path, contents: &str, version: u32) -> Result<String> {
+    let mut document: toml_edit::DocumentMut = contents
+        .parse()
+        .map_err(|e: toml_edit::TomlError| Error::config(path, contents, e.message(), e.span()))?;
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut document);
+    }
+    Ok(
+        match document.get_mut("version").and_then(|v| v.as_value_mut()) {
+            Some(old) => {
+                let decor = old.decor().clone();
+                *old = i64::from(CONFIG_VERSION).into();
+                *old.decor_mut() = decor;
+                document.to_string()
+            }
+            // Inserting would put the key after the file's other top-level keys.
+            None => format!("version = {}\n\n{}", CONFIG_VERSION, document),
+        },
+    )
+}
+
+/// Read the file at `path`, naming it in the error.
+fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| Error::io(path, e))
+}
+
+/// Deserialize the TOML `contents` of `path`, keeping the span of any error.
+fn parse_toml<T: DeserializeOwned>(path: &Path, contents: &str) -> Result<T> {
+    toml::from_str(contents).map_err(|e| Error::config(path, contents, e.message(), e.span()))
+}
+
+/// Read `path` as a TOML document for editing, apply `edit` and write it back.
+fn edit_toml(path: &Path, edit: impl FnOnce(&mut toml_edit::DocumentMut)) -> Result<()> {
+    let contents = read_file(path)?;
+    let mut doc: toml_edit::DocumentMut = contents
+        .parse()
+        .map_err(|e: toml_edit::TomlError| Error::config(path, &contents, e.message(), e.span()))?;
+    edit(&mut doc);
+    fs::write(path, doc.to_string()).map_err(|e| Error::io(path, e))
 }
 
 pub fn save_course_config(course_dir: &Path, config: &CourseConfig) -> io::Result<()> {
     let path = course_dir.join(COURSE_CONFIG_FILENAME);
-    let contents =
-        toml::to_string_pretty(config).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let contents = toml::to_string_pretty(config).map_err(io::Error::other)?;
     fs::write(&path, contents)
 }
 
+/// Edit `course.toml` in place, preserving comments and formatting.
+pub fn edit_course_config(
+    course_dir: &Path,
+    edit: impl FnOnce(&mut toml_edit::DocumentMut),
+) -> Result<()> {
+    edit_toml(&course_dir.join(COURSE_CONFIG_FILENAME), edit)
+}
+
+/// Edit an exercise's `meta.toml` in place, preserving comments and formatting.
+pub fn edit_meta(
+    exercise_dir: &Path,
+    edit: impl FnOnce(&mut toml_edit::DocumentMut),
+) -> Result<()> {
+    edit_toml(&exercise_dir.join(META_FILENAME), edit)
+}
+
 pub fn find_course_root(start: &Path) -> Option<PathBuf> {
     start
         .ancestors()
@@ -162,68 +780,231 @@ pub fn find_course_root(start: &Path) -> Option<PathBuf> {
         .map(Path::to_path_buf)
 }
 
-/// Parse a course code into (faculty, course_code).
+/// Split a course code into faculty and number using the configured
+/// `code_rules`. If no rule matches, the faculty is `undefined`.
+pub fn parse_course_code(global: &GlobalConfig, input: &str) -> ParsedCode {
+    course_code::parse(&global.code_rules, input)
+}
+
+/// Resolve a course code to its expected directory path under `layout`.
+pub fn resolve_course_dir(global: &GlobalConfig, input: &str) -> PathBuf {
+    let parsed = parse_course_code(global, input);
+    course_dir_for(global, input, (parsed.faculty, parsed.number))
+}
+
+/// Directory for course `code` split into `(faculty, number)`, following `layout`.
+pub fn course_dir_for(global: &GlobalConfig, code: &str, split: (String, String)) -> PathBuf {
+    let (faculty, number) = split;
+    let mut values = HashMap::from([
+        ("code", code.to_string()),
+        ("faculty", faculty),
+        ("number", number),
+    ]);
+    if let Some(term) = &global.active_term {
+        values.insert("term", term.clone());
+    }
+    expand_tilde(&global.courses_dir).join(global.layout.path(&values))
+}
+
+/// The faculty of a course: taken from its path when it follows `layout`,
+/// otherwise parsed from the code.
+pub fn course_faculty(global: &GlobalConfig, course_dir: &Path, code: &str) -> String {
+    course_dir
+        .strip_prefix(expand_tilde(&global.courses_dir))
+        .ok()
+        .and_then(|relative| global.layout.parse(relative))
+        .and_then(|mut values| values.remove("faculty"))
+        .unwrap_or_else(|| parse_course_code(global, code).faculty)
+}
+
+/// Iterate course directories (those containing a `course.toml`).
 ///
-/// Rules:
-/// 1. If input contains a hyphen: split on first `-`. Faculty = left, code = right.
-/// 2. Else: faculty = longest leading alphabetical substring, code = rest.
-/// 3. If no leading alpha: faculty = "undefined", code = full input.
-pub fn parse_course_code(input: &str) -> (String, String) {
-    if let Some(pos) = input.find('-') {
-        let faculty = &input[..pos];
-        let code = &input[pos + 1..];
-        return (faculty.to_string(), code.to_string());
+/// Follows `layout` by default; with `discovery_depth` set, any directory
+/// containing a `course.toml` up to that depth is a course. Unless `all` is
+/// set, only active courses are returned (see `is_active`); with `all`, the
+/// archive directory is scanned too.
+pub fn course_dirs(global: &GlobalConfig, all: bool) -> impl Iterator<Item = PathBuf> {
+    let mut roots = vec![expand_tilde(&global.courses_dir)];
+    if all {
+        roots.extend(global.archive_dir.as_deref().map(expand_tilde));
     }
 
-    let alpha_len = input.chars().take_while(|c| c.is_ascii_alphabetic()).count();
-    if alpha_len > 0 && alpha_len < input.len() {
-        let faculty = &input[..alpha_len];
-        let code = &input[alpha_len..];
-        return (faculty.to_string(), code.to_string());
+    let mut dirs = Vec::new();
+    for root in &roots {
+        match global.discovery_depth {
+            Some(depth) => discover_courses(root, depth, &mut dirs),
+            None => layout_courses(root, global.layout.depth(), &mut dirs),
+        }
     }
+    if !all {
+        // Courses whose config can't be read are kept so errors still surface.
+        dirs.retain(|dir| {
+            load_course_config(dir)
+                .map(|config| is_active(global, dir, &config.course))
+                .unwrap_or(true)
+        });
+    }
+    dirs.into_iter()
+}
 
-    ("undefined".to_string(), input.to_string())
+/// Whether a course belongs to the active term and has not been archived.
+///
+/// Courses without a `term`, or any course when no `active_term` is set,
+/// count as active.
+pub fn is_active(global: &GlobalConfig, course_dir: &Path, course: &CourseInfo) -> bool {
+    !is_archived(global, course_dir, course)
+        && match (&global.active_term, &course.term) {
+            (Some(active), Some(term)) => active == term,
+            _ => true,
+        }
 }
 
-/// Resolve a course code to its expected directory path: `<courses_dir>/<faculty>/<course_code>/`
-pub fn resolve_course_dir(global: &GlobalConfig, input: &str) -> PathBuf {
-    let (faculty, code) = parse_course_code(input);
-    expand_tilde(&global.courses_dir).join(faculty).join(code)
+/// Whether a course is marked `archived` or lives under `archive_dir`.
+pub fn is_archived(global: &GlobalConfig, course_dir: &Path, course: &CourseInfo) -> bool {
+    course.archived
+        || global
+            .archive_dir
+            .as_deref()
+            .is_some_and(|dir| course_dir.starts_with(expand_tilde(dir)))
 }
 
-/// Iterate all course directories under `<courses_dir>/<faculty>/<course>/`.
-pub fn course_dirs(global: &GlobalConfig) -> impl Iterator<Item = PathBuf> {
-    let courses_dir = expand_tilde(&global.courses_dir);
-    fs::read_dir(courses_dir)
+fn subdirs(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    fs::read_dir(dir)
         .into_iter()
         .flatten()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
-        .flat_map(|faculty| {
-            fs::read_dir(faculty.path())
-                .into_iter()
-                .flatten()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
-        })
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
         .map(|e| e.path())
 }
 
-/// Find the course directory for a given input (code or human-readable name).
+fn layout_courses(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    if depth == 0 {
+        if dir.join(COURSE_CONFIG_FILENAME).exists() {
+            out.push(dir.to_path_buf());
+        }
+        return;
+    }
+    for sub in subdirs(dir) {
+        layout_courses(&sub, depth - 1, out);
+    }
+}
+
+fn discover_courses(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    for sub in subdirs(dir) {
+        if sub.join(COURSE_CONFIG_FILENAME).exists() {
+            out.push(sub);
+        } else if depth > 1 {
+            discover_courses(&sub, depth - 1, out);
+        }
+    }
+}
+
+/// Find the course directory for a given input (code, name or alias).
 ///
-/// First tries code-based path resolution, then falls back to scanning all
-/// course.toml files for a matching `name`.
-pub fn find_course_dir(global: &GlobalConfig, input: &str) -> Option<PathBuf> {
+/// First tries code-based path resolution, then matches the input against
+/// the code, name and aliases of every course (see `course_candidates`).
+/// Inactive courses are only found with `all`.
+pub fn find_course_dir(global: &GlobalConfig, input: &str, all: bool) -> Result<PathBuf> {
     let dir = resolve_course_dir(global, input);
     if dir.join(COURSE_CONFIG_FILENAME).exists() {
-        return Some(dir);
+        // Report a broken course.toml here rather than "not found" below.
+        let config = load_course_config(&dir)?;
+        if all || is_active(global, &dir, &config.course) {
+            return Ok(dir);
+        }
     }
 
-    course_dirs(global).find(|dir| {
-        fs::read_to_string(dir.join(COURSE_CONFIG_FILENAME))
-            .ok()
-            .and_then(|contents| toml::from_str::<CourseConfig>(&contents).ok())
-            .and_then(|config| config.course.name)
-            .is_some_and(|name| name == input)
+    matching::select("course", input, course_candidates(global, input, all)).map_err(|e| match e {
+        SelectError::NotFound => Error::CourseNotFound(input.to_string()),
+        SelectError::Ambiguous(candidates) => Error::AmbiguousCourse {
+            input: input.to_string(),
+            candidates,
+        },
+        SelectError::Io(e) => e.into(),
     })
 }
+
+/// Courses whose code, name or aliases match `input`, best match first.
+/// Matching ignores case and accepts prefixes and fuzzy abbreviations.
+pub fn course_candidates(global: &GlobalConfig, input: &str, all: bool) -> Vec<Candidate<PathBuf>> {
+    let courses = course_dirs(global, all).filter_map(|dir| {
+        let course = load_course_config(&dir).ok()?.course;
+        let label = match &course.name {
+            Some(name) => format!("{}  {}", course.code, name),
+            None => course.code.clone(),
+        };
+        let keys = std::iter::once(course.code)
+            .chain(course.name)
+            .chain(course.aliases)
+            .collect();
+        Some((dir, label, keys))
+    });
+    matching::rank(input, courses)
+}
+
+/// The course `input` names, if one matches better than the others. Unlike
+/// `find_course_dir`, this never asks the user to choose.
+pub fn unique_course_dir(global: &GlobalConfig, input: &str, all: bool) -> Option<PathBuf> {
+    matching::unique(course_candidates(global, input, all))
+}
+
+/// Find an exercise of a course by directory name, number (`3` → `ex03`),
+/// prefix, fuzzy match or the `title` in its `meta.toml`.
+pub fn find_exercise_dir(course_dir: &Path, config: &CourseConfig, input: &str) -> Result<PathBuf> {
+    let dir = course_dir.join(input);
+    if exercise_dirs(course_dir).any(|exercise| exercise == dir) {
+        return Ok(dir);
+    }
+
+    matching::select(
+        "exercise",
+        input,
+        exercise_candidates(course_dir, config, input),
+    )
+    .map_err(|e| match e {
+        SelectError::NotFound => Error::ExerciseNotFound {
+            course: config.course.code.clone(),
+            input: input.to_string(),
+        },
+        SelectError::Ambiguous(candidates) => Error::AmbiguousExercise {
+            input: input.to_string(),
+            candidates,
+        },
+        SelectError::Io(e) => e.into(),
+    })
+}
+
+/// Exercises of a course matching `input`, best match first.
+pub fn exercise_candidates(
+    course_dir: &Path,
+    config: &CourseConfig,
+    input: &str,
+) -> Vec<Candidate<PathBuf>> {
+    // A bare number matches the exercise numbered so by its naming scheme.
+    let query = match input.parse::<u32>() {
+        Ok(n) => n.to_string(),
+        Err(_) => input.to_string(),
+    };
+    // Several types' schemes may read a number from the same name. All of
+    // them are keys, so the match doesn't depend on which type is tried first.
+    let mut types: Vec<_> = config.exercise_types.iter().collect();
+    types.sort_by_key(|(name, _)| *name);
+    let exercises = exercise_dirs(course_dir).filter_map(|dir| {
+        let name = dir.file_name()?.to_string_lossy().into_owned();
+        let title = exercise_title(&dir);
+        let numbers: Vec<String> = types
+            .iter()
+            .filter_map(|(_, t)| t.naming_scheme.number(&name))
+            .map(|n| n.to_string())
+            .collect();
+
+        let label = match &title {
+            Some(title) => format!("{}  {}", name, title),
+            None => name.clone(),
+        };
+        let keys = std::iter::once(name).chain(title).chain(numbers).collect();
+        Some((dir, label, keys))
+    });
+    matching::rank(&query, exercises)
+}
@@ -0,0 +1,284 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Faculty used when no rule can split a course code.
+pub const UNDEFINED_FACULTY: &str = "undefined";
+
+/// A named rule for splitting a course code into faculty and number.
+///
+/// Rules are tried in order; the first one that matches wins:
+///
+/// ```toml
+/// [[code_rules]]
+/// name = "aalto"
+/// pattern = '^(?P<faculty>[A-Z]+)-(?P<code>[A-Z]\d+)'
+///
+/// [[code_rules]]
+/// name = "known"
+/// faculties = ["ELEC", "CS", "MS"]
+///
+/// [[code_rules]]
+/// name = "fallback"
+/// builtin = "separator"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawCodeRule", into = "RawCodeRule")]
+pub struct CodeRule {
+    pub name: String,
+    pub kind: RuleKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum RuleKind {
+    /// Regex with named `faculty` and `code` captures.
+    Pattern(Regex),
+    /// Known faculty prefixes, optionally followed by a separator.
+    Faculties(Vec<String>),
+    Builtin(Builtin),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Builtin {
+    /// Split at the first `-`, `.`, `_` or space (`MS-C2286`, `CS.E4000`).
+    Separator,
+    /// Split after the leading letters (`MATH101`).
+    Letters,
+}
+
+/// A rule as written in `config.toml`: exactly one of `pattern`,
+/// `faculties` or `builtin`.
+#[derive(Serialize, Deserialize)]
+struct RawCodeRule {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    faculties: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    builtin: Option<Builtin>,
+}
+
+/// The result of splitting a course code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCode {
+    pub faculty: String,
+    pub number: String,
+    /// Name of the rule that matched, `None` for the `undefined` fallback.
+    pub rule: Option<String>,
+}
+
+impl CodeRule {
+    /// Split `input`, or `None` if this rule does not apply.
+    pub fn apply(&self, input: &str) -> Option<(String, String)> {
+        match &self.kind {
+            RuleKind::Pattern(pattern) => {
+                let captures = pattern.captures(input)?;
+                let number = captures.name("code")?.as_str();
+                let faculty = captures
+                    .name("faculty")
+                    .map_or(UNDEFINED_FACULTY, |m| m.as_str());
+                non_empty(faculty, number)
+            }
+            RuleKind::Faculties(faculties) => {
+                let mut prefixes: Vec<&String> = faculties.iter().collect();
+                prefixes.sort_by_key(|p| std::cmp::Reverse(p.len()));
+                prefixes.into_iter().find_map(|prefix| {
+                    let rest = strip_prefix_ignore_case(input, prefix)?;
+                    let number = rest.strip_prefix(is_separator).unwrap_or(rest);
+                    non_empty(prefix, number)
+                })
+            }
+            RuleKind::Builtin(Builtin::Separator) => {
+                let (faculty, number) = input.split_once(is_separator)?;
+                non_empty(faculty, number)
+            }
+            RuleKind::Builtin(Builtin::Letters) => {
+                let alpha_len = input
+                    .find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(input.len());
+                non_empty(&input[..alpha_len], &input[alpha_len..])
+            }
+        }
+    }
+}
+
+/// The rules used when `code_rules` is not configured: the built-in
+/// separator and leading-letters heuristics.
+pub fn default_rules() -> Vec<CodeRule> {
+    [("separator", Builtin::Separator), ("letters", Builtin::Letters)]
+        .into_iter()
+        .map(|(name, builtin)| CodeRule {
+            name: name.to_string(),
+            kind: RuleKind::Builtin(builtin),
+        })
+        .collect()
+}
+
+/// Split `input` with the first matching rule, falling back to the
+/// `undefined` faculty.
+pub fn parse(rules: &[CodeRule], input: &str) -> ParsedCode {
+    rules
+        .iter()
+        .find_map(|rule| {
+            rule.apply(input).map(|(faculty, number)| ParsedCode {
+                faculty,
+                number,
+                rule: Some(rule.name.clone()),
+            })
+        })
+        .unwrap_or_else(|| ParsedCode {
+            faculty: UNDEFINED_FACULTY.to_string(),
+            number: input.to_string(),
+            rule: None,
+        })
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '-' | '.' | '_' | ' ')
+}
+
+fn non_empty(faculty: &str, number: &str) -> Option<(String, String)> {
+    (!faculty.is_empty() && !number.is_empty()).then(|| (faculty.to_string(), number.to_string()))
+}
+
+fn strip_prefix_ignore_case<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = input.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &input[prefix.len()..])
+}
+
+impl TryFrom<RawCodeRule> for CodeRule {
+    type Error = String;
+
+    fn try_from(raw: RawCodeRule) -> Result<Self, Self::Error> {
+        let invalid = |reason: String| format!("invalid code rule '{}': {}", raw.name, reason);
+        let kind = match (raw.pattern, raw.faculties, raw.builtin) {
+            (Some(pattern), None, None) => {
+                let regex = Regex::new(&pattern).map_err(|e| invalid(e.to_string()))?;
+                if !regex.capture_names().flatten().any(|name| name == "code") {
+                    return Err(invalid(
+                        "pattern needs a named 'code' capture, e.g. (?P<code>...)".into(),
+                    ));
+                }
+                RuleKind::Pattern(regex)
+            }
+            (None, Some(faculties), None) => RuleKind::Faculties(faculties),
+            (None, None, Some(builtin)) => RuleKind::Builtin(builtin),
+            _ => {
+                return Err(invalid(
+                    "expected exactly one of 'pattern', 'faculties' or 'builtin'".into(),
+                ));
+            }
+        };
+        Ok(Self {
+            name: raw.name,
+            kind,
+        })
+    }
+}
+
+impl From<CodeRule> for RawCodeRule {
+    fn from(rule: CodeRule) -> Self {
+        let mut raw = RawCodeRule {
+            name: rule.name,
+            pattern: None,
+            faculties: None,
+            builtin: None,
+        };
+        match rule.kind {
+            RuleKind::Pattern(regex) => raw.pattern = Some(regex.as_str().to_string()),
+            RuleKind::Faculties(faculties) => raw.faculties = Some(faculties),
+            RuleKind::Builtin(builtin) => raw.builtin = Some(builtin),
+        }
+        raw
+    }
+}
+
+impl fmt::Display for CodeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            RuleKind::Pattern(regex) => write!(f, "pattern {}", regex.as_str()),
+            RuleKind::Faculties(faculties) => write!(f, "faculties {}", faculties.join(", ")),
+            RuleKind::Builtin(Builtin::Separator) => f.write_str("builtin separator"),
+            RuleKind::Builtin(Builtin::Letters) => f.write_str("builtin letters"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Rules {
+        code_rules: Vec<CodeRule>,
+    }
+
+    fn rules(toml: &str) -> Result<Vec<CodeRule>, toml::de::Error> {
+        toml::from_str::<Rules>(toml).map(|rules| rules.code_rules)
+    }
+
+    fn split(rules: &[CodeRule], input: &str) -> (String, String, Option<String>) {
+        let parsed = parse(rules, input);
+        (parsed.faculty, parsed.number, parsed.rule)
+    }
+
+    #[test]
+    fn default_rules_split_at_separator_then_letters() {
+        let rules = default_rules();
+        assert_eq!(
+            split(&rules, "CS-E4000-2"),
+            ("CS".into(), "E4000-2".into(), Some("separator".into()))
+        );
+        assert_eq!(
+            split(&rules, "MATH101"),
+            ("MATH".into(), "101".into(), Some("letters".into()))
+        );
+        assert_eq!(
+            split(&rules, "12345"),
+            (UNDEFINED_FACULTY.into(), "12345".into(), None)
+        );
+    }
+
+    #[test]
+    fn configured_rules_are_tried_in_order() {
+        let rules = rules(
+            r#"
+            [[code_rules]]
+            name = "aalto"
+            pattern = '^(?P<faculty>[A-Z]+)-(?P<code>[A-Z]\d+)$'
+
+            [[code_rules]]
+            name = "known"
+            faculties = ["CS", "CSE"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            split(&rules, "MS-C2286"),
+            ("MS".into(), "C2286".into(), Some("aalto".into()))
+        );
+        assert_eq!(
+            split(&rules, "CS-E4000-2"),
+            ("CS".into(), "E4000-2".into(), Some("known".into()))
+        );
+        assert_eq!(
+            split(&rules, "cse4000"),
+            ("CSE".into(), "4000".into(), Some("known".into()))
+        );
+        assert_eq!(
+            split(&rules, "CS"),
+            (UNDEFINED_FACULTY.into(), "CS".into(), None)
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(rules("[[code_rules]]\nname = 'x'\npattern = '^(?P<faculty>[A-Z]+)'").is_err());
+        assert!(rules("[[code_rules]]\nname = 'x'\npattern = '('").is_err());
+        assert!(rules("[[code_rules]]\nname = 'x'\nbuiltin = 'letters'\nfaculties = []").is_err());
+        assert!(rules("[[code_rules]]\nname = 'x'").is_err());
+    }
+}
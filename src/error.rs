@@ -0,0 +1,381 @@
+use std::fmt::{self, Write};
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned by the `study` library.
+///
+/// `render` formats an error for the terminal, with the offending line of a
+/// config file and a hint, and `exit_code` gives the CLI's exit status.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// A TOML file could not be parsed or has invalid values.
+    Config(Box<ConfigError>),
+    /// No course matches the name given.
+    CourseNotFound(String),
+    /// Several courses match the name given equally well.
+    AmbiguousCourse {
+        input: String,
+        candidates: Vec<String>,
+    },
+    /// The path is not inside a course directory.
+    NotInCourse(PathBuf),
+    /// No exercise of the course matches the name given.
+    ExerciseNotFound { course: String, input: String },
+    /// Several exercises match the name given equally well.
+    AmbiguousExercise {
+        input: String,
+        candidates: Vec<String>,
+    },
+    /// The course's `course.toml` defines no exercise types.
+    NoExerciseTypes { course: String },
+    /// The course defines no exercise type of this name.
+    UnknownExerciseType { name: String, available: Vec<String> },
+    /// The course defines several exercise types and none was chosen.
+    ExerciseTypeRequired { available: Vec<String> },
+    /// No template of this name is on the search path.
+    TemplateMissing {
+        name: String,
+        searched: Vec<PathBuf>,
+        available: Vec<String>,
+    },
+    /// A template's `extends` chain leads back to itself.
+    TemplateCycle { name: String, parent: String },
+    /// The exercise directory to create already exists.
+    ExerciseExists(PathBuf),
+}
+
+/// A TOML file that failed to parse or deserialize.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub message: String,
+    /// Byte range of the offending part of `contents`, if known.
+    pub span: Option<Range<usize>>,
+    /// The file's contents, for showing the offending line.
+    pub contents: String,
+    pub hint: Option<String>,
+}
+
+impl Error {
+    /// An I/O error on `path`.
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Io {
+            path: Some(path.into()),
+            source,
+        }
+    }
+
+    /// A TOML error in the file at `path` with the given `contents`.
+    pub fn config(
+        path: &Path,
+        contents: &str,
+        message: impl Into<String>,
+        span: Option<Range<usize>>,
+    ) -> Self {
+        Error::Config(Box::new(ConfigError {
+            path: path.to_path_buf(),
+            message: message.into().trim_end().to_string(),
+            span,
+            contents: contents.to_string(),
+            hint: None,
+        }))
+    }
+
+    /// Attach a hint to a config error. Other errors have hints of their own.
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        if let Error::Config(config) = &mut self {
+            config.hint = Some(hint.into());
+        }
+        self
+    }
+
+    /// The `Error` wrapped in `e` by `From<Error> for io::Error`, if any.
+    pub fn from_io(e: &io::Error) -> Option<&Error> {
+        e.get_ref()?.downcast_ref()
+    }
+
+    /// The process exit status for this error.
+    ///
+    /// | code | meaning                                   |
+    /// |------|-------------------------------------------|
+    /// | 3    | course not found                          |
+    /// | 4    | ambiguous course or exercise name         |
+    /// | 5    | exercise not found                        |
+    /// | 6    | missing, unknown or unspecified exercise type |
+    /// | 7    | template missing or inheriting from itself |
+    /// | 73   | exercise already exists (`EX_CANTCREAT`)  |
+    /// | 74   | I/O error (`EX_IOERR`)                    |
+    /// | 78   | invalid config file (`EX_CONFIG`)         |
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::CourseNotFound(_) | Error::NotInCourse(_) => 3,
+            Error::AmbiguousCourse { .. } | Error::AmbiguousExercise { .. } => 4,
+            Error::ExerciseNotFound { .. } => 5,
+            Error::NoExerciseTypes { .. }
+            | Error::UnknownExerciseType { .. }
+            | Error::ExerciseTypeRequired { .. } => 6,
+            Error::TemplateMissing { .. } | Error::TemplateCycle { .. } => 7,
+            Error::ExerciseExists(_) => 73,
+            Error::Io { .. } => 74,
+            Error::Config(_) => 78,
+        }
+    }
+
+    /// The closest `io::ErrorKind`, used when converting to `io::Error`.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Error::Io { source, .. } => source.kind(),
+            Error::Config(_) | Error::NoExerciseTypes { .. } | Error::TemplateCycle { .. } => {
+                io::ErrorKind::InvalidData
+            }
+            Error::CourseNotFound(_)
+            | Error::NotInCourse(_)
+            | Error::ExerciseNotFound { .. }
+            | Error::UnknownExerciseType { .. }
+            | Error::TemplateMissing { .. } => io::ErrorKind::NotFound,
+            Error::AmbiguousCourse { .. }
+            | Error::AmbiguousExercise { .. }
+            | Error::ExerciseTypeRequired { .. } => io::ErrorKind::InvalidInput,
+            Error::ExerciseExists(_) => io::ErrorKind::AlreadyExists,
+        }
+    }
+
+    /// A suggestion for fixing the error.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Error::Io { path, source } => match (source.kind(), path) {
+                (io::ErrorKind::PermissionDenied, Some(path)) => {
+                    Some(format!("check the permissions of {}", path.display()))
+                }
+                _ => None,
+            },
+            Error::Config(config) => config.hint.clone(),
+            Error::CourseNotFound(input) => Some(format!(
+                "`study list --all` shows every course; `study init course {}` creates it",
+                input
+            )),
+            Error::AmbiguousCourse { .. } => {
+                Some("use more of the name, or the full course code".into())
+            }
+            Error::NotInCourse(_) => {
+                Some("pass --course <name> or run from inside a course directory".into())
+            }
+            Error::ExerciseNotFound { course, .. } => {
+                Some(format!("`study list {}` shows its exercises", course))
+            }
+            Error::AmbiguousExercise { .. } => {
+                Some("use more of the name, or the exercise's directory name".into())
+            }
+            Error::NoExerciseTypes { .. } => {
+                Some("add an [exercise_types.<name>] section to course.toml".into())
+            }
+            Error::UnknownExerciseType { available, .. }
+            | Error::ExerciseTypeRequired { available } => {
+                Some(format!("use --type with one of: {}", available.join(", ")))
+            }
+            Error::TemplateMissing {
+                name,
+                searched,
+                available,
+            } => {
+                let create = searched
+                    .first()
+                    .map(|dir| format!("create {}", dir.join(name).display()));
+                let choose = (!available.is_empty())
+                    .then(|| format!("use one of: {}", available.join(", ")));
+                match (create, choose) {
+                    (Some(create), Some(choose)) => Some(format!("{}, or {}", create, choose)),
+                    (create, choose) => create.or(choose),
+                }
+            }
+            Error::TemplateCycle { .. } => {
+                Some("remove the `extends` that closes the loop in template.toml".into())
+            }
+            Error::ExerciseExists(_) => Some("pass --name to choose another name".into()),
+        }
+    }
+
+    /// Format the error for the terminal: the message, the offending line of
+    /// a config file with the span underlined, and a hint.
+    pub fn render(&self) -> String {
+        self.render_as("error")
+    }
+
+    /// Like `render`, for problems that are reported but not fatal.
+    pub fn render_warning(&self) -> String {
+        self.render_as("warning")
+    }
+
+    fn render_as(&self, severity: &str) -> String {
+        let mut out = match self {
+            Error::Config(config) => {
+                format!("{}: {}\n{}", severity, config.message, config.snippet())
+            }
+            _ => format!("{}: {}\n", severity, self),
+        };
+        if let Some(hint) = self.hint() {
+            let _ = writeln!(out, "  hint: {}", hint);
+        }
+        out
+    }
+}
+
+impl ConfigError {
+    /// One-based line and column of the start of the span.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        let start = self.span.as_ref()?.start.min(self.contents.len());
+        let before = &self.contents[..start];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rfind('\n')
+            .map_or(before, |newline| &before[newline + 1..])
+            .chars()
+            .count()
+            + 1;
+        Some((line, column))
+    }
+
+    /// The location and the offending line with the span underlined.
+    fn snippet(&self) -> String {
+        let (Some(span), Some((line, column))) = (&self.span, self.location()) else {
+            return format!("  --> {}\n", self.path.display());
+        };
+        let text = self.contents.lines().nth(line - 1).unwrap_or("");
+        let number = line.to_string();
+        let pad = " ".repeat(number.len());
+        let width = self.contents[span.start.min(self.contents.len())..span.end.min(self.contents.len())]
+            .lines()
+            .next()
+            .map_or(1, |s| s.chars().count().max(1));
+        format!(
+            "{pad}--> {}:{}:{}\n{pad} |\n{} | {}\n{pad} | {}{}\n",
+            self.path.display(),
+            line,
+            column,
+            number,
+            text,
+            " ".repeat(column - 1),
+            "^".repeat(width),
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: {}", path.display(), source),
+            Error::Io { path: None, source } => write!(f, "{}", source),
+            Error::Config(config) => match config.location() {
+                Some((line, column)) => write!(
+                    f,
+                    "{}:{}:{}: {}",
+                    config.path.display(),
+                    line,
+                    column,
+                    config.message
+                ),
+                None => write!(f, "{}: {}", config.path.display(), config.message),
+            },
+            Error::CourseNotFound(input) => write!(f, "Course not found: {}", input),
+            Error::AmbiguousCourse { input, candidates } => {
+                write_ambiguous(f, input, "course", candidates)
+            }
+            Error::NotInCourse(path) => write!(
+                f,
+                "No course.toml found in {} or its parent directories.",
+                path.display()
+            ),
+            Error::ExerciseNotFound { course, input } => {
+                write!(f, "Exercise not found in {}: {}", course, input)
+            }
+            Error::AmbiguousExercise { input, candidates } => {
+                write_ambiguous(f, input, "exercise", candidates)
+            }
+            Error::NoExerciseTypes { course } => {
+                write!(f, "No exercise types defined in {}'s course.toml.", course)
+            }
+            Error::UnknownExerciseType { name, available } => write!(
+                f,
+                "Unknown exercise type '{}'. Available: {}",
+                name,
+                available.join(", ")
+            ),
+            Error::ExerciseTypeRequired { available } => write!(
+                f,
+                "Multiple exercise types available: {}",
+                available.join(", ")
+            ),
+            Error::TemplateMissing { name, searched, .. } => {
+                let searched: Vec<String> = searched
+                    .iter()
+                    .map(|dir| dir.display().to_string())
+                    .chain(std::iter::once("built-in".to_string()))
+                    .collect();
+                write!(
+                    f,
+                    "Template '{}' not found. Searched: {}",
+                    name,
+                    searched.join(", ")
+                )
+            }
+            Error::TemplateCycle { name, parent } => write!(
+                f,
+                "Template '{}' extends itself through '{}'",
+                name, parent
+            ),
+            Error::ExerciseExists(path) => {
+                write!(f, "Exercise directory already exists: {}", path.display())
+            }
+        }
+    }
+}
+
+fn write_ambiguous(
+    f: &mut fmt::Formatter,
+    input: &str,
+    what: &str,
+    candidates: &[String],
+) -> fmt::Result {
+    write!(f, "'{}' matches {} {}s:", input, candidates.len(), what)?;
+    for candidate in candidates {
+        write!(f, "\n  {}", candidate)?;
+    }
+    Ok(())
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Error::Io { path: None, source }
+    }
+}
+
+/// Keeps the `Error` inside, so `Error::from_io` can recover it.
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io { path: None, source } => source,
+            e => io::Error::new(e.kind(), e),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::config::{load_state, save_state, state_path};
+use crate::ProcessGroup;
+use chrono::{DateTime, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+const HISTORY_FILENAME: &str = "history.jsonl";
+
+/// How often a session watcher checks whether the tracked commands are still running.
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many times in a row a session watcher may fail to read the history
+/// before it gives up.
+const WATCH_RETRIES: u32 = 12;
+
+/// One `study` invocation, appended to the history file as a JSON line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// Start time in milliseconds since the epoch, unique per session.
+    pub id: i64,
+    pub course: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exercise: Option<String>,
+    pub start: DateTime<Local>,
+    /// Set when the tracked commands exit or on `study stop`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Local>>,
+    /// Process groups of the tracked commands.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pids: Vec<ProcessGroup>,
+}
+
+/// A line of the history file. Sessions are never rewritten; their end is
+/// appended as a separate line, so concurrent writers only ever append.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Line {
+    Session(SessionRecord),
+    End { id: i64, end: DateTime<Local> },
+}
+
+impl SessionRecord {
+    pub fn new(course: &str, exercise: Option<&str>) -> Self {
+        let start = Local::now();
+        Self {
+            id: start.timestamp_millis(),
+            course: course.to_string(),
+            exercise: exercise.map(String::from),
+            start,
+            end: None,
+            pids: Vec::new(),
+        }
+    }
+
+    /// True if the session has not ended and its commands are still running.
+    pub fn running(&self) -> bool {
+        self.end.is_none() && self.pids.iter().any(|group| group.signal(0))
+    }
+
+    /// Time spent in the session. Running sessions count until now; sessions
+    /// that were never closed (e.g. after a reboot) count as zero.
+    pub fn duration(&self) -> TimeDelta {
+        match self.end {
+            Some(end) => end - self.start,
+            None if self.running() => Local::now() - self.start,
+            None => TimeDelta::zero(),
+        }
+    }
+}
+
+pub fn history_path() -> PathBuf {
+    state_path().with_file_name(HISTORY_FILENAME)
+}
+
+/// All recorded sessions, oldest first, with their ends filled in.
+pub fn load_history() -> io::Result<Vec<SessionRecord>> {
+    let path = history_path();
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut history: Vec<SessionRecord> = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line = serde_json::from_str(line).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: {}", path.display(), i + 1, e),
+            )
+        })?;
+        match line {
+            Line::Session(record) => history.push(record),
+            // The first end wins if `study stop` and the watcher both record one.
+            Line::End { id, end } => {
+                if let Some(record) = history.iter_mut().find(|r| r.id == id && r.end.is_none()) {
+                    record.end = Some(end);
+                }
+            }
+        }
+    }
+    Ok(history)
+}
+
+pub fn append(record: &SessionRecord) -> io::Result<()> {
+    append_line(record)
+}
+
+/// Record the end of session `id`, unless it has already ended.
+pub fn finish(id: i64) -> io::Result<()> {
+    let history = load_history()?;
+    if !history.iter().any(|r| r.id == id && r.end.is_none()) {
+        return Ok(());
+    }
+    #[derive(Serialize)]
+    struct End {
+        id: i64,
+        end: DateTime<Local>,
+    }
+    append_line(&End {
+        id,
+        end: Local::now(),
+    })
+}
+
+/// Append `value` as one line. Lines are written with a single `write` to a
+/// file opened for appending, so lines from concurrent writers don't mix.
+fn append_line(value: &impl Serialize) -> io::Result<()> {
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(value).map_err(io::Error::other)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())
+}
+
+/// Start a background `study` process that ends session `id` once its
+/// tracked commands have exited.
+pub fn spawn_watcher(id: i64) -> io::Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    Command::new(std::env::current_exe()?)
+        .arg("watch-session")
+        .arg(id.to_string())
+        .process_group(0)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// Wait until the tracked commands of session `id` have exited, then end it.
+/// A history that can't be read, e.g. while another process is appending to
+/// it, is retried a few times.
+pub fn watch(id: i64) -> io::Result<()> {
+    let mut failures = 0;
+    loop {
+        let history = match load_history() {
+            Ok(history) => {
+                failures = 0;
+                history
+            }
+            Err(_) if failures < WATCH_RETRIES => {
+                failures += 1;
+                thread::sleep(WATCH_INTERVAL);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        match history.iter().find(|r| r.id == id) {
+            Some(record) if record.running() => thread::sleep(WATCH_INTERVAL),
+            Some(_) => {
+                finish(id)?;
+                return forget_session(id);
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Forget the process groups of session `id` once it has ended, so that a
+/// later `study stop` has nothing to signal.
+fn forget_session(id: i64) -> io::Result<()> {
+    let mut state = load_state();
+    if state.session_id != Some(id) {
+        return Ok(());
+    }
+    state.session_pids.clear();
+    state.session_id = None;
+    save_state(&state)
+}
+
+/// Format a duration as e.g. `1h 05m`, `12m` or `40s`.
+pub fn format_duration(duration: TimeDelta) -> String {
+    let seconds = duration.num_seconds().max(0);
+    match (seconds / 3600, seconds / 60 % 60) {
+        (0, 0) => format!("{}s", seconds),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {:02}m", hours, minutes),
+    }
+}
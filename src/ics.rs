@@ -0,0 +1,327 @@
+//! Reading and writing the parts of iCalendar (RFC 5545) files that carry deadlines.
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// The product identifier written into exported calendars.
+const PRODUCT_ID: &str = "-//study//study//EN";
+
+/// Content lines longer than this many octets are folded.
+const LINE_LIMIT: usize = 75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Event,
+    Todo,
+}
+
+/// A `VEVENT` or `VTODO` read from a calendar.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub kind: EntryKind,
+    pub uid: Option<String>,
+    pub summary: String,
+    pub description: Option<String>,
+    /// `DUE` of a to-do, or the start date of an event.
+    pub due: Option<NaiveDate>,
+}
+
+/// Parameters of a content line, e.g. `VALUE=DATE`, with upper-cased names.
+type Params = Vec<(String, String)>;
+
+/// An all-day deadline to write into a calendar.
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub date: NaiveDate,
+}
+
+/// Parse the events and to-dos in an iCalendar file.
+pub fn parse(text: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut current: Option<Entry> = None;
+    let mut depth = 0usize;
+
+    for (number, line) in unfold(text) {
+        let Some((name, params, value)) = split_line(&line) else {
+            return Err(format!("line {}: expected NAME:VALUE", number));
+        };
+        match name.as_str() {
+            "BEGIN" => {
+                let kind = match value.to_ascii_uppercase().as_str() {
+                    "VEVENT" => Some(EntryKind::Event),
+                    "VTODO" => Some(EntryKind::Todo),
+                    _ => None,
+                };
+                if current.is_some() {
+                    depth += 1;
+                } else if let Some(kind) = kind {
+                    current = Some(Entry {
+                        kind,
+                        uid: None,
+                        summary: String::new(),
+                        description: None,
+                        due: None,
+                    });
+                }
+            }
+            "END" if current.is_some() => {
+                if depth > 0 {
+                    depth -= 1;
+                } else {
+                    entries.extend(current.take());
+                }
+            }
+            _ => {
+                // Properties of nested components such as alarms are ignored.
+                let Some(entry) = current.as_mut().filter(|_| depth == 0) else {
+                    continue;
+                };
+                match name.as_str() {
+                    "UID" => entry.uid = Some(unescape(value)),
+                    "SUMMARY" => entry.summary = unescape(value),
+                    "DESCRIPTION" => entry.description = Some(unescape(value)),
+                    "DUE" if entry.kind == EntryKind::Todo => {
+                        entry.due = Some(
+                            parse_date(&params, value)
+                                .map_err(|e| format!("line {}: {}", number, e))?,
+                        );
+                    }
+                    "DTSTART" if entry.kind == EntryKind::Event => {
+                        entry.due = Some(
+                            parse_date(&params, value)
+                                .map_err(|e| format!("line {}: {}", number, e))?,
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if current.is_some() {
+        return Err("unterminated component at end of file".into());
+    }
+    Ok(entries)
+}
+
+/// Write `deadlines` as a calendar of all-day events.
+pub fn write(name: &str, deadlines: &[Deadline]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODUCT_ID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape(name)));
+    for deadline in deadlines {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", escape(&deadline.uid)));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        push_line(
+            &mut out,
+            &format!("DTSTART;VALUE=DATE:{}", deadline.date.format("%Y%m%d")),
+        );
+        if let Some(end) = deadline.date.succ_opt() {
+            push_line(
+                &mut out,
+                &format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+            );
+        }
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&deadline.summary)));
+        if let Some(description) = &deadline.description {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape(description)));
+        }
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Join folded content lines, keeping the number of the line each starts on.
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push((i + 1, line.to_string())),
+        }
+    }
+    lines
+}
+
+/// Split a content line into its upper-cased name, its parameters and its value.
+fn split_line(line: &str) -> Option<(String, Params, &str)> {
+    // The value starts at the first colon outside a quoted parameter value.
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((
+                key.to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+    Some((name, params, value))
+}
+
+/// Parse a `DATE` or `DATE-TIME` value as a local date. UTC times are converted
+/// to local time; floating times and times with a `TZID` are taken as written.
+fn parse_date(params: &Params, value: &str) -> Result<NaiveDate, String> {
+    let value = value.trim();
+    let is_date = params
+        .iter()
+        .any(|(key, value)| key == "VALUE" && value.eq_ignore_ascii_case("DATE"));
+    if is_date || !value.contains('T') {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map_err(|_| format!("invalid date '{}'", value));
+    }
+    let invalid = || format!("invalid date-time '{}'", value);
+    match value.strip_suffix(['Z', 'z']) {
+        Some(utc) => {
+            let time =
+                NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+            Ok(Utc
+                .from_utc_datetime(&time)
+                .with_timezone(&Local)
+                .date_naive())
+        }
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .map(|time| time.date())
+            .map_err(|_| invalid()),
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Append `line` with CRLF, folding it so no physical line exceeds the limit.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn folded_lines_are_joined() {
+        let text = "BEGIN:VCALENDAR\r\n\
+                    BEGIN:VEVENT\r\n\
+                    UID:ex1@example\r\n\
+                    DTSTART;VALUE=\r\n \
+                    DATE:20261017\r\n\
+                    SUMMARY:MS-C2286 Exercise 1\\, part\r\n\
+                    \t a\r\n\
+                    BEGIN:VALARM\r\n\
+                    DESCRIPTION:Reminder\r\n\
+                    END:VALARM\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VTODO\r\n\
+                    SUMMARY:Report\r\n\
+                    DUE:20261101T235900\r\n\
+                    END:VTODO\r\n\
+                    END:VCALENDAR\r\n";
+        let entries = parse(text).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, EntryKind::Event);
+        assert_eq!(entries[0].uid.as_deref(), Some("ex1@example"));
+        assert_eq!(entries[0].summary, "MS-C2286 Exercise 1, part a");
+        assert_eq!(entries[0].description, None);
+        assert_eq!(entries[0].due, Some(date(2026, 10, 17)));
+        assert_eq!(entries[1].kind, EntryKind::Todo);
+        assert_eq!(entries[1].due, Some(date(2026, 11, 1)));
+    }
+
+    #[test]
+    fn malformed_calendars_name_the_line() {
+        let text = "BEGIN:VEVENT\nSUMMARY:x\nDTSTART:2026-10-17\nEND:VEVENT\n";
+        assert_eq!(
+            parse(text).unwrap_err(),
+            "line 3: invalid date '2026-10-17'"
+        );
+        assert_eq!(
+            parse("BEGIN:VEVENT\nno colon\n").unwrap_err(),
+            "line 2: expected NAME:VALUE"
+        );
+        assert!(parse("BEGIN:VTODO\nSUMMARY:x\n").is_err());
+    }
+
+    #[test]
+    fn written_calendars_fold_and_read_back() {
+        let deadline = Deadline {
+            uid: "MS-C2286/ex01@study".into(),
+            summary: format!("MS-C2286 ex01: {}", "Määräaika; ".repeat(10)),
+            description: Some("line one\nline two".into()),
+            date: date(2026, 10, 17),
+        };
+        let text = write("Deadlines", std::slice::from_ref(&deadline));
+        assert!(text.split("\r\n").all(|line| line.len() <= LINE_LIMIT));
+
+        let entries = parse(&text).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].uid.as_deref(), Some(deadline.uid.as_str()));
+        assert_eq!(entries[0].summary, deadline.summary);
+        assert_eq!(entries[0].description, deadline.description);
+        assert_eq!(entries[0].due, Some(deadline.date));
+    }
+}
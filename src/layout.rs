@@ -0,0 +1,259 @@
+use chrono::{Datelike, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+pub const DEFAULT_LAYOUT: &str = "{faculty}/{number}";
+
+const PLACEHOLDERS: &[&str] = &["faculty", "number", "code", "year", "term"];
+
+/// Where courses live under `courses_dir`, e.g. `{year}/{term}/{faculty}/{code}`.
+///
+/// Placeholders:
+/// - `{code}`: the full course code as given (`MS-C2286`)
+/// - `{faculty}`, `{number}`: the two halves from `parse_course_code` (`MS`, `C2286`)
+/// - `{year}`: the current year
+/// - `{term}`: the global `active_term`, or else `spring` (January–July) or
+///   `autumn` (August–December)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CourseLayout {
+    source: String,
+    components: Vec<Vec<Part>>,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Literal(String),
+    Placeholder(String),
+}
+
+impl CourseLayout {
+    /// Number of directory levels below `courses_dir`.
+    pub fn depth(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Build the relative course path for the given placeholder values.
+    pub fn path(&self, values: &HashMap<&str, String>) -> PathBuf {
+        self.components
+            .iter()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .map(|part| match part {
+                        Part::Literal(text) => text.clone(),
+                        Part::Placeholder(name) => values
+                            .get(name.as_str())
+                            .cloned()
+                            .unwrap_or_else(|| current_value(name)),
+                    })
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    /// Recover placeholder values from a course path relative to `courses_dir`.
+    pub fn parse(&self, relative: &Path) -> Option<HashMap<String, String>> {
+        let names: Vec<String> = relative
+            .components()
+            .map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        if names.len() != self.components.len() {
+            return None;
+        }
+
+        let mut values = HashMap::new();
+        for (parts, name) in self.components.iter().zip(&names) {
+            match_parts(parts, name, &mut values).then_some(())?;
+        }
+        Some(values)
+    }
+}
+
+/// Value of a date-derived placeholder for a course created today.
+fn current_value(name: &str) -> String {
+    let today = Local::now().date_naive();
+    match name {
+        "year" => today.year().to_string(),
+        "term" if today.month() <= 7 => "spring".to_string(),
+        "term" => "autumn".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn match_parts(parts: &[Part], input: &str, values: &mut HashMap<String, String>) -> bool {
+    let Some((part, rest)) = parts.split_first() else {
+        return input.is_empty();
+    };
+
+    match part {
+        Part::Literal(text) => input
+            .strip_prefix(text.as_str())
+            .is_some_and(|remaining| match_parts(rest, remaining, values)),
+        Part::Placeholder(name) => {
+            for len in (1..=input.len())
+                .rev()
+                .filter(|&len| input.is_char_boundary(len))
+            {
+                values.insert(name.clone(), input[..len].to_string());
+                if match_parts(rest, &input[len..], values) {
+                    return true;
+                }
+            }
+            values.remove(name);
+            false
+        }
+    }
+}
+
+impl FromStr for CourseLayout {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| format!("invalid layout '{}': {}", source, reason);
+
+        let components = source
+            .split('/')
+            .map(|component| {
+                if component.is_empty() || component == "." || component == ".." {
+                    return Err(invalid(format!("invalid path component '{}'", component)));
+                }
+                let mut parts = Vec::new();
+                let mut rest = component;
+                while let Some(start) = rest.find('{') {
+                    if start > 0 {
+                        parts.push(Part::Literal(rest[..start].to_string()));
+                    }
+                    let end = rest[start..]
+                        .find('}')
+                        .ok_or_else(|| invalid("unclosed '{'".into()))?;
+                    let name = &rest[start + 1..start + end];
+                    if !PLACEHOLDERS.contains(&name) {
+                        return Err(invalid(format!(
+                            "unknown placeholder '{{{}}}' (expected one of {})",
+                            name,
+                            PLACEHOLDERS.join(", ")
+                        )));
+                    }
+                    parts.push(Part::Placeholder(name.to_string()));
+                    rest = &rest[start + end + 1..];
+                }
+                if rest.contains('}') {
+                    return Err(invalid("unmatched '}'".into()));
+                }
+                if !rest.is_empty() {
+                    parts.push(Part::Literal(rest.to_string()));
+                }
+                Ok(parts)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let has = |name: &str| {
+            components
+                .iter()
+                .flatten()
+                .any(|p| matches!(p, Part::Placeholder(n) if n == name))
+        };
+        if !has("code") && !has("number") {
+            return Err(invalid("must contain '{code}' or '{number}'".into()));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            components,
+        })
+    }
+}
+
+impl Default for CourseLayout {
+    fn default() -> Self {
+        DEFAULT_LAYOUT.parse().expect("default layout is valid")
+    }
+}
+
+impl TryFrom<String> for CourseLayout {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        source.parse()
+    }
+}
+
+impl From<CourseLayout> for String {
+    fn from(layout: CourseLayout) -> Self {
+        layout.source
+    }
+}
+
+impl fmt::Display for CourseLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(source: &str) -> CourseLayout {
+        source.parse().unwrap()
+    }
+
+    #[test]
+    fn path_and_parse_round_trip() {
+        let layout = layout("{year}/{term}/{faculty}/{code}");
+        let values = HashMap::from([
+            ("year", "2026".to_string()),
+            ("term", "autumn".to_string()),
+            ("faculty", "CS".to_string()),
+            ("code", "CS-E4000".to_string()),
+        ]);
+        let path = layout.path(&values);
+        assert_eq!(path, Path::new("2026/autumn/CS/CS-E4000"));
+        assert_eq!(layout.depth(), 4);
+
+        let parsed = layout.parse(&path).unwrap();
+        assert_eq!(parsed["term"], "autumn");
+        assert_eq!(parsed["code"], "CS-E4000");
+        assert_eq!(layout.parse(Path::new("2026/autumn/CS")), None);
+        assert_eq!(layout.parse(Path::new("2026/../CS/CS-E4000")), None);
+    }
+
+    #[test]
+    fn placeholders_take_as_much_as_the_rest_allows() {
+        let layout = layout("{code}-{year}");
+        let parsed = layout.parse(Path::new("CS-E4000-2-2026")).unwrap();
+        assert_eq!(parsed["code"], "CS-E4000-2");
+        assert_eq!(parsed["year"], "2026");
+        assert_eq!(layout.parse(Path::new("2026")), None);
+    }
+
+    #[test]
+    fn default_layout_uses_faculty_and_number() {
+        let parsed = CourseLayout::default()
+            .parse(Path::new("MS/C2286"))
+            .unwrap();
+        assert_eq!(parsed["faculty"], "MS");
+        assert_eq!(parsed["number"], "C2286");
+    }
+
+    #[test]
+    fn invalid_layouts_are_rejected() {
+        for source in [
+            "{faculty}",
+            "{faculty}/../{code}",
+            "{nope}/{code}",
+            "{code",
+            "{code}}",
+            "a//{code}",
+        ] {
+            assert!(source.parse::<CourseLayout>().is_err(), "{}", source);
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! The course model behind the `study` CLI: configuration, course and
+//! exercise discovery, templates, naming schemes and study history.
+//!
+//! Start from `Workspace`, which loads the global config and hands out
+//! `Course`s and `Exercise`s. The modules below expose the lower-level
+//! functions the CLI is built from.
+
+pub mod config;
+pub mod course_code;
+pub mod error;
+pub mod history;
+pub mod ics;
+pub mod layout;
+mod matching;
+pub mod naming;
+pub mod schema;
+mod session;
+pub mod template;
+pub mod workspace;
+
+pub use config::{
+    CourseConfig, CourseInfo, ExerciseInfo, ExerciseMeta, ExerciseStatus, ExerciseType,
+    GlobalConfig, StudyState,
+};
+pub use error::{Error, Result};
+pub use matching::{Candidate, Quality, rank};
+pub use session::ProcessGroup;
+pub use workspace::{Course, CreatedExercise, Exercise, NewExercise, UnknownKeys, Workspace};
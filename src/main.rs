@@ -1,51 +1,58 @@
 mod cli;
 mod commands;
 mod completions;
-mod config;
 
-use clap::Parser;
-use cli::{Args, Command, InitTarget};
-use config::load_global_config;
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
+use cli::{Args, Command};
+use std::io;
+use study::{Error, UnknownKeys, Workspace};
 
 fn main() {
+    CompleteEnv::with_factory(Args::command).complete();
+
     let args = Args::parse();
-    let global = match load_global_config() {
-        Ok(c) => c,
+    // Runs before the global config is loaded, so it can report errors in it.
+    if let Some(Command::Doctor { fix }) = args.command {
+        if let Err(e) = commands::doctor::run(args.strict, fix) {
+            std::process::exit(report(&e));
+        }
+        return;
+    }
+    let unknown_keys = if args.strict {
+        UnknownKeys::Deny
+    } else {
+        UnknownKeys::Warn
+    };
+    let workspace = match Workspace::load_with(unknown_keys) {
+        Ok(workspace) => workspace,
         Err(e) => {
-            eprintln!("Error loading global config: {}", e);
-            std::process::exit(1);
+            eprint!("{}", e.render());
+            std::process::exit(e.exit_code());
         }
     };
 
-    let result = match args.command {
-        Some(Command::Init { target }) => match target {
-            InitTarget::Course { name, raw } => {
-                commands::init_course::run(&global, name.as_deref(), raw)
-            }
-            InitTarget::Exercise {
-                r#type,
-                name,
-                course,
-            } => {
-                commands::init_exercise::run(
-                    &global,
-                    r#type.as_deref(),
-                    name.as_deref(),
-                    course.as_deref(),
-                )
-            }
-        },
-        Some(Command::Study { course, exercise }) => {
-            commands::study::run(&global, Some(&course), exercise.as_deref())
+    let result = commands::run(&workspace, args);
+
+    for warning in workspace.take_warnings() {
+        eprint!("{}", warning.render_warning());
+    }
+    if let Err(e) = result {
+        std::process::exit(report(&e));
+    }
+}
+
+/// Print `e` with its hint and return the exit status for it. Errors from
+/// the library carry their own status; other OS errors exit with `EX_IOERR`.
+fn report(e: &io::Error) -> i32 {
+    match Error::from_io(e) {
+        Some(error) => {
+            eprint!("{}", error.render());
+            error.exit_code()
         }
-        Some(Command::Completions { shell }) => commands::completions::run(shell),
         None => {
-            commands::study::run(&global, args.course.as_deref(), args.exercise.as_deref())
+            eprintln!("error: {}", e);
+            if e.raw_os_error().is_some() { 74 } else { 1 }
         }
-    };
-
-    if let Err(e) = result {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
     }
 }
@@ -0,0 +1,281 @@
+use std::cmp::Ordering;
+use std::io::{self, IsTerminal, Write};
+
+/// How well a query matches a key, best first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quality {
+    Exact,
+    IgnoreCase,
+    Prefix,
+    Substring,
+    /// The query's characters appear in order; the value counts skipped characters.
+    Fuzzy(usize),
+}
+
+impl Quality {
+    /// Matches in the same tier are equally good and make a lookup ambiguous.
+    fn tier(self) -> Quality {
+        match self {
+            Quality::Fuzzy(_) => Quality::Fuzzy(0),
+            quality => quality,
+        }
+    }
+}
+
+/// A possible match for a lookup, with the label shown to the user.
+#[derive(Debug)]
+pub struct Candidate<T> {
+    pub value: T,
+    pub label: String,
+    pub quality: Quality,
+}
+
+/// Compare `query` to `key`, ignoring case for everything but `Exact`.
+pub fn quality(query: &str, key: &str) -> Option<Quality> {
+    if query == key {
+        return Some(Quality::Exact);
+    }
+    let query = query.to_lowercase();
+    let key = key.to_lowercase();
+    if query.is_empty() {
+        None
+    } else if query == key {
+        Some(Quality::IgnoreCase)
+    } else if key.starts_with(&query) {
+        Some(Quality::Prefix)
+    } else if key.contains(&query) {
+        Some(Quality::Substring)
+    } else {
+        subsequence_gaps(&query, &key).map(Quality::Fuzzy)
+    }
+}
+
+/// Number of characters of `key` skipped to find `query` as a subsequence.
+fn subsequence_gaps(query: &str, key: &str) -> Option<usize> {
+    let mut key_chars = key.chars();
+    let mut gaps = 0;
+    for q in query.chars() {
+        loop {
+            match key_chars.next() {
+                Some(k) if k == q => break,
+                Some(_) => gaps += 1,
+                None => return None,
+            }
+        }
+    }
+    Some(gaps)
+}
+
+/// Match `query` against each item's keys, keeping the best key per item,
+/// and sort the matches best first.
+pub fn rank<T>(
+    query: &str,
+    items: impl IntoIterator<Item = (T, String, Vec<String>)>,
+) -> Vec<Candidate<T>> {
+    let mut ranked: Vec<Candidate<T>> = items
+        .into_iter()
+        .filter_map(|(value, label, keys)| {
+            let quality = keys.iter().filter_map(|key| quality(query, key)).min()?;
+            Some(Candidate {
+                value,
+                label,
+                quality,
+            })
+        })
+        .collect();
+    ranked.sort_by(|a, b| match a.quality.cmp(&b.quality) {
+        Ordering::Equal => a.label.cmp(&b.label),
+        ordering => ordering,
+    });
+    ranked
+}
+
+/// The best candidate, if no other candidate is as good.
+pub fn unique<T>(mut ranked: Vec<Candidate<T>>) -> Option<T> {
+    let ambiguous = ranked
+        .get(1)
+        .is_some_and(|second| second.quality.tier() == ranked[0].quality.tier());
+    if ranked.is_empty() || ambiguous {
+        return None;
+    }
+    Some(ranked.swap_remove(0).value)
+}
+
+/// Why `select` found no single match.
+#[derive(Debug)]
+pub enum SelectError {
+    /// Nothing matches the query.
+    NotFound,
+    /// Several candidates match equally well; their labels, best first.
+    Ambiguous(Vec<String>),
+    /// Asking the user to choose failed or was cancelled.
+    Io(io::Error),
+}
+
+/// Pick the match for `query` among `ranked` candidates.
+///
+/// A unique best match is returned directly. Otherwise the user chooses
+/// between the equally good matches when running interactively; if not, the
+/// error lists the candidates.
+pub fn select<T>(what: &str, query: &str, mut ranked: Vec<Candidate<T>>) -> Result<T, SelectError> {
+    let Some(best) = ranked.first().map(|c| c.quality.tier()) else {
+        return Err(SelectError::NotFound);
+    };
+    let tied = ranked.iter().take_while(|c| c.quality.tier() == best).count();
+    if tied == 1 {
+        return Ok(ranked.swap_remove(0).value);
+    }
+    ranked.truncate(tied);
+
+    if io::stdin().is_terminal() && io::stderr().is_terminal() {
+        let index = pick(what, query, &ranked).map_err(SelectError::Io)?;
+        return Ok(ranked.swap_remove(index).value);
+    }
+
+    Err(SelectError::Ambiguous(
+        ranked.into_iter().map(|c| c.label).collect(),
+    ))
+}
+
+/// Ask the user to choose one of `candidates` by number.
+fn pick<T>(what: &str, query: &str, candidates: &[Candidate<T>]) -> io::Result<usize> {
+    eprintln!("'{}' matches {} {}s:", query, candidates.len(), what);
+    for (i, candidate) in candidates.iter().enumerate() {
+        eprintln!("  {}) {}", i + 1, candidate.label);
+    }
+    loop {
+        eprint!("Choose [1-{}]: ", candidates.len());
+        io::stderr().flush()?;
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                format!("No {} chosen.", what),
+            ));
+        }
+        match input.trim().parse::<usize>() {
+            Ok(n) if (1..=candidates.len()).contains(&n) => return Ok(n - 1),
+            _ => eprintln!("Enter a number between 1 and {}.", candidates.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CourseConfig, exercise_candidates};
+    use std::fs;
+
+    /// Items whose value and label are their first key.
+    fn items(keys: &[&[&str]]) -> Vec<(String, String, Vec<String>)> {
+        keys.iter()
+            .map(|keys| {
+                let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+                (keys[0].clone(), keys[0].clone(), keys)
+            })
+            .collect()
+    }
+
+    fn labels<T>(ranked: &[Candidate<T>]) -> Vec<&str> {
+        ranked.iter().map(|c| c.label.as_str()).collect()
+    }
+
+    #[test]
+    fn quality_orders_exact_before_fuzzy() {
+        assert_eq!(quality("ex01", "ex01"), Some(Quality::Exact));
+        assert_eq!(quality("EX01", "ex01"), Some(Quality::IgnoreCase));
+        assert_eq!(quality("Ex", "ex01"), Some(Quality::Prefix));
+        assert_eq!(quality("01", "ex01"), Some(Quality::Substring));
+        assert_eq!(quality("la", "Linear Algebra"), Some(Quality::Fuzzy(3)));
+        assert_eq!(quality("al", "ex01"), None);
+        assert_eq!(quality("", "ex01"), None);
+    }
+
+    #[test]
+    fn rank_keeps_the_best_key_of_each_item() {
+        let courses = items(&[
+            &["MS-C2286", "Linalg"],
+            &["MS-A0011", "Linear Algebra", "la"],
+            &["CS-A1110", "Programming 1"],
+        ]);
+        let ranked = rank("la", courses);
+        assert_eq!(labels(&ranked), ["MS-A0011", "MS-C2286"]);
+        assert_eq!(ranked[0].quality, Quality::Exact);
+        assert_eq!(ranked[1].quality, Quality::Fuzzy(2));
+    }
+
+    #[test]
+    fn equal_matches_are_ties_sorted_by_label() {
+        let ranked = rank("ms", items(&[&["MS-C2286"], &["MS-A0011"], &["CS-A1110"]]));
+        assert_eq!(labels(&ranked), ["MS-A0011", "MS-C2286"]);
+        assert_eq!(unique(ranked), None);
+
+        // Fuzzy matches tie however many characters they skip.
+        let ranked = rank("mc", items(&[&["MS-C2286"], &["Mathematics"]]));
+        assert_ne!(ranked[0].quality, ranked[1].quality);
+        assert_eq!(unique(ranked), None);
+
+        let ranked = rank("ms-a", items(&[&["MS-A0011"], &["MS-C2286-a"]]));
+        assert_eq!(unique(ranked).as_deref(), Some("MS-A0011"));
+    }
+
+    #[test]
+    fn select_returns_a_unique_best_match_without_asking() {
+        let ranked = rank("ex", items(&[&["ex01"], &["next"]]));
+        assert_eq!(select("exercise", "ex", ranked).unwrap(), "ex01");
+        assert!(matches!(
+            select("exercise", "zz", rank("zz", items(&[&["ex01"]]))),
+            Err(SelectError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn exercise_numbers_come_from_every_naming_scheme() {
+        let course_dir =
+            std::env::temp_dir().join(format!("study-matching-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&course_dir);
+        for name in ["ex01", "week42-ex01", "week42-ex02", "notes"] {
+            fs::create_dir_all(course_dir.join(name)).unwrap();
+        }
+        let config: CourseConfig = toml::from_str(
+            r#"
+            [course]
+            code = "MS-C2286"
+
+            [exercise_types.ex]
+            template = "empty"
+            naming_scheme = "ex{:02}"
+
+            [exercise_types.weekly]
+            template = "empty"
+            naming_scheme = "week{week:02}-ex{n:02}"
+
+            [exercise_types.review]
+            template = "empty"
+            naming_scheme = "week{n:02}-ex01"
+            "#,
+        )
+        .unwrap();
+        let find = |input: &str| {
+            labels(&exercise_candidates(&course_dir, &config, input))
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+
+        // `week42-ex01` is exercise 1 of week 42 and review 42.
+        assert_eq!(find("42"), ["week42-ex01", "week42-ex02"]);
+        assert_eq!(
+            unique(exercise_candidates(&course_dir, &config, "42")),
+            Some(course_dir.join("week42-ex01"))
+        );
+        assert_eq!(find("01")[..2], ["ex01", "week42-ex01"]);
+        assert_eq!(unique(exercise_candidates(&course_dir, &config, "1")), None);
+        assert_eq!(
+            unique(exercise_candidates(&course_dir, &config, "2")),
+            Some(course_dir.join("week42-ex02"))
+        );
+        fs::remove_dir_all(course_dir).unwrap();
+    }
+}
@@ -0,0 +1,366 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fs, io};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A parsed `ExerciseType::naming_scheme` such as `week{week:02}-ex{n:02}`.
+///
+/// Placeholders are `{name}` or `{name:spec}`:
+/// - `{date}` / `{date:<strftime>}`: today's date (default `%Y-%m-%d`).
+/// - `{week}` / `{week:0N}`: today's ISO week number.
+/// - anything else (including the unnamed `{}` / `{:0N}`) is a counter. A
+///   counter spec is a zero-padded width (`0N`), `a` for lowercase letters
+///   or `A` for uppercase letters.
+///
+/// The last counter is incremented when creating an exercise. Each counter
+/// is numbered independently for every combination of the placeholders
+/// before it, so `week{week:02}-ex{n:02}` restarts `n` every week. Earlier
+/// counters keep their highest existing value. A scheme without any
+/// placeholder gets an implicit `{:02}` suffix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NamingScheme {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Counter(CounterStyle),
+    Date(String),
+    Week(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CounterStyle {
+    Numeric(usize),
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(u32),
+    Text(String),
+}
+
+impl NamingScheme {
+    /// Compute the next exercise name given the entries already in `course_dir`.
+    pub fn next_name(&self, course_dir: &Path) -> io::Result<String> {
+        let existing: Vec<Vec<Value>> = fs::read_dir(course_dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| self.parse(&e.file_name().to_string_lossy()))
+            .collect();
+        Ok(self.next_name_from(&existing, Local::now().date_naive()))
+    }
+
+    /// The value of the last counter in `name`, if it matches this scheme.
+    pub fn number(&self, name: &str) -> Option<u32> {
+        let values = self.parse(name)?;
+        let index = self.last_counter()?;
+        match values.get(self.placeholder_index(index)) {
+            Some(Value::Number(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn next_name_from(&self, existing: &[Vec<Value>], today: NaiveDate) -> String {
+        let last_counter = self.last_counter();
+        let mut chosen: Vec<Value> = Vec::new();
+        let mut name = String::new();
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            let value = match segment {
+                Segment::Literal(text) => {
+                    name.push_str(text);
+                    continue;
+                }
+                Segment::Date(format) => Value::Text(today.format(format).to_string()),
+                Segment::Week(_) => Value::Number(today.iso_week().week()),
+                Segment::Counter(_) => {
+                    let depth = chosen.len();
+                    let max = existing
+                        .iter()
+                        .filter(|values| values[..depth] == chosen[..])
+                        .filter_map(|values| match values[depth] {
+                            Value::Number(n) => Some(n),
+                            Value::Text(_) => None,
+                        })
+                        .max();
+                    let n = if Some(i) == last_counter {
+                        max.map_or(1, |n| n + 1)
+                    } else {
+                        max.unwrap_or(1)
+                    };
+                    Value::Number(n)
+                }
+            };
+            name.push_str(&format_value(segment, &value));
+            chosen.push(value);
+        }
+
+        name
+    }
+
+    /// Match `name` against the scheme, returning one value per placeholder.
+    fn parse(&self, name: &str) -> Option<Vec<Value>> {
+        let mut values = Vec::new();
+        match_segments(&self.segments, name, &mut values).then_some(values)
+    }
+
+    fn last_counter(&self) -> Option<usize> {
+        self.segments
+            .iter()
+            .rposition(|s| matches!(s, Segment::Counter(_)))
+    }
+
+    /// Position of segment `index` among the placeholders.
+    fn placeholder_index(&self, index: usize) -> usize {
+        self.segments[..index]
+            .iter()
+            .filter(|s| !matches!(s, Segment::Literal(_)))
+            .count()
+    }
+}
+
+fn format_value(segment: &Segment, value: &Value) -> String {
+    match (segment, value) {
+        (Segment::Counter(CounterStyle::Numeric(width)) | Segment::Week(width), Value::Number(n)) => {
+            format!("{:0width$}", n, width = *width)
+        }
+        (Segment::Counter(CounterStyle::Lower), Value::Number(n)) => letters(*n),
+        (Segment::Counter(CounterStyle::Upper), Value::Number(n)) => letters(*n).to_uppercase(),
+        (_, Value::Text(text)) => text.clone(),
+        (_, Value::Number(n)) => n.to_string(),
+    }
+}
+
+/// Bijective base-26: 1 → a, 26 → z, 27 → aa.
+fn letters(mut n: u32) -> String {
+    let mut out = Vec::new();
+    while n > 0 {
+        n -= 1;
+        out.push(b'a' + (n % 26) as u8);
+        n /= 26;
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+fn parse_letters(s: &str) -> Option<u32> {
+    s.bytes().try_fold(0u32, |acc, b| {
+        acc.checked_mul(26)?
+            .checked_add(u32::from(b.to_ascii_lowercase() - b'a') + 1)
+    })
+}
+
+fn match_segments(segments: &[Segment], input: &str, values: &mut Vec<Value>) -> bool {
+    let Some((segment, rest)) = segments.split_first() else {
+        return input.is_empty();
+    };
+
+    if let Segment::Literal(text) = segment {
+        return input
+            .strip_prefix(text.as_str())
+            .is_some_and(|remaining| match_segments(rest, remaining, values));
+    }
+
+    let accepts = |c: char| match segment {
+        Segment::Counter(CounterStyle::Numeric(_)) | Segment::Week(_) => c.is_ascii_digit(),
+        Segment::Counter(CounterStyle::Lower) => c.is_ascii_lowercase(),
+        Segment::Counter(CounterStyle::Upper) => c.is_ascii_uppercase(),
+        _ => true,
+    };
+    let max_len = input
+        .char_indices()
+        .find(|(_, c)| !accepts(*c))
+        .map_or(input.len(), |(i, _)| i);
+
+    // Prefer the longest run; backtrack if the rest of the scheme doesn't match.
+    for len in (1..=max_len).rev().filter(|&len| input.is_char_boundary(len)) {
+        let text = &input[..len];
+        let value = match segment {
+            Segment::Counter(CounterStyle::Numeric(_)) | Segment::Week(_) => {
+                text.parse().ok().map(Value::Number)
+            }
+            Segment::Counter(_) => parse_letters(text).map(Value::Number),
+            _ => Some(Value::Text(text.to_string())),
+        };
+        let Some(value) = value else { continue };
+
+        values.push(value);
+        if match_segments(rest, &input[len..], values) {
+            return true;
+        }
+        values.pop();
+    }
+
+    false
+}
+
+fn parse_placeholder(body: &str) -> Result<Segment, String> {
+    let (name, spec) = body.split_once(':').unwrap_or((body, ""));
+    match name {
+        "date" => {
+            let format = if spec.is_empty() { DEFAULT_DATE_FORMAT } else { spec };
+            if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(format!("invalid date format '{}'", format));
+            }
+            if format.contains('/') {
+                return Err(format!("date format '{}' would produce a '/'", format));
+            }
+            Ok(Segment::Date(format.to_string()))
+        }
+        "week" => parse_width(spec).map(Segment::Week),
+        _ if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+            Err(format!("invalid placeholder name '{}'", name))
+        }
+        _ => match spec {
+            "a" => Ok(Segment::Counter(CounterStyle::Lower)),
+            "A" => Ok(Segment::Counter(CounterStyle::Upper)),
+            _ => parse_width(spec).map(|w| Segment::Counter(CounterStyle::Numeric(w))),
+        },
+    }
+}
+
+fn parse_width(spec: &str) -> Result<usize, String> {
+    if spec.is_empty() {
+        return Ok(0);
+    }
+    spec.strip_prefix('0')
+        .unwrap_or(spec)
+        .parse()
+        .map_err(|_| format!("invalid width '{}' (expected e.g. '02')", spec))
+}
+
+impl FromStr for NamingScheme {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| format!("invalid naming scheme '{}': {}", source, reason);
+
+        let mut segments = Vec::new();
+        let mut rest = source;
+        while !rest.is_empty() {
+            match rest.find(['{', '}']) {
+                Some(pos) if rest[pos..].starts_with('}') => {
+                    return Err(invalid("unmatched '}'".into()));
+                }
+                Some(pos) => {
+                    if pos > 0 {
+                        segments.push(Segment::Literal(rest[..pos].to_string()));
+                    }
+                    let end = rest[pos..]
+                        .find('}')
+                        .ok_or_else(|| invalid("unclosed '{'".into()))?;
+                    let body = &rest[pos + 1..pos + end];
+                    segments.push(parse_placeholder(body).map_err(invalid)?);
+                    rest = &rest[pos + end + 1..];
+                }
+                None => {
+                    segments.push(Segment::Literal(rest.to_string()));
+                    rest = "";
+                }
+            }
+        }
+
+        if segments.iter().all(|s| matches!(s, Segment::Literal(_))) {
+            segments.push(Segment::Counter(CounterStyle::Numeric(2)));
+        } else if !segments.iter().any(|s| matches!(s, Segment::Counter(_))) {
+            return Err(invalid("at least one counter (e.g. '{n:02}') is required".into()));
+        }
+
+        if source.contains('/') {
+            return Err(invalid("names cannot contain '/'".into()));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+}
+
+impl TryFrom<String> for NamingScheme {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        source.parse()
+    }
+}
+
+impl From<NamingScheme> for String {
+    fn from(scheme: NamingScheme) -> Self {
+        scheme.source
+    }
+}
+
+impl fmt::Display for NamingScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheme(source: &str) -> NamingScheme {
+        source.parse().unwrap()
+    }
+
+    fn next(scheme: &NamingScheme, existing: &[&str], today: NaiveDate) -> String {
+        let existing: Vec<Vec<Value>> = existing
+            .iter()
+            .filter_map(|name| scheme.parse(name))
+            .collect();
+        scheme.next_name_from(&existing, today)
+    }
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn counter_restarts_every_week() {
+        let scheme = scheme("week{week:02}-ex{n:02}");
+        let existing = ["week41-ex03", "week42-ex01", "week42-ex02", "notes"];
+        assert_eq!(next(&scheme, &existing, day(2026, 10, 14)), "week42-ex03");
+        assert_eq!(next(&scheme, &existing, day(2026, 10, 19)), "week43-ex01");
+        assert_eq!(scheme.number("week42-ex02"), Some(2));
+        assert_eq!(scheme.number("week42"), None);
+    }
+
+    #[test]
+    fn letter_counters_continue_past_z() {
+        let lower = scheme("hw-{n:a}");
+        assert_eq!(next(&lower, &[], day(2026, 1, 1)), "hw-a");
+        assert_eq!(next(&lower, &["hw-b", "hw-z"], day(2026, 1, 1)), "hw-aa");
+        assert_eq!(lower.number("hw-aa"), Some(27));
+        assert_eq!(lower.number("hw-A"), None);
+
+        let upper = scheme("{n:A}");
+        assert_eq!(next(&upper, &["AZ"], day(2026, 1, 1)), "BA");
+    }
+
+    #[test]
+    fn scheme_without_placeholders_gets_a_counter() {
+        let scheme = scheme("ex");
+        assert_eq!(next(&scheme, &["ex09"], day(2026, 1, 1)), "ex10");
+        assert_eq!(scheme.number("ex10"), Some(10));
+        assert_eq!(scheme.to_string(), "ex");
+    }
+
+    #[test]
+    fn invalid_schemes_are_rejected() {
+        for source in ["ex{n", "ex}", "{week}-{date}", "a/{n}", "{n:x}"] {
+            assert!(source.parse::<NamingScheme>().is_err(), "{}", source);
+        }
+    }
+}
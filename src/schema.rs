@@ -0,0 +1,655 @@
+//! The structure of `config.toml`, `course.toml` and `meta.toml`, for
+//! reporting misspelled or unsupported keys that serde would otherwise
+//! ignore, and for generating JSON Schemas for editors.
+//!
+//! The shapes mirror the serde structs in `config`; the tests below fail
+//! when a field is added to a struct but not here.
+
+use crate::error::Error;
+use serde_json::{Map, Value, json};
+use std::ops::Range;
+use std::path::Path;
+use toml_edit::{ImDocument, Item, TableLike, TomlError};
+
+/// The values allowed for a TOML key.
+#[derive(Debug)]
+pub enum Shape {
+    /// Any value.
+    Any,
+    String,
+    Integer,
+    /// An integer or float.
+    Number,
+    Boolean,
+    /// A TOML date or a `YYYY-MM-DD` string.
+    Date,
+    /// One of these strings.
+    Enum(&'static [&'static str]),
+    /// A table with exactly these keys.
+    Table(&'static [Field]),
+    /// A table with these keys and any others.
+    OpenTable(&'static [Field]),
+    /// A table with arbitrary keys whose values have this shape.
+    Map(&'static Shape),
+    /// An array whose elements have this shape.
+    Array(&'static Shape),
+    /// A value of any of these shapes.
+    OneOf(&'static [Shape]),
+}
+
+/// A key of a table.
+#[derive(Debug)]
+pub struct Field {
+    pub name: &'static str,
+    pub shape: Shape,
+    pub description: &'static str,
+    pub required: bool,
+}
+
+const fn field(name: &'static str, shape: Shape, description: &'static str) -> Field {
+    Field {
+        name,
+        shape,
+        description,
+        required: false,
+    }
+}
+
+const fn required(name: &'static str, shape: Shape, description: &'static str) -> Field {
+    Field {
+        name,
+        shape,
+        description,
+        required: true,
+    }
+}
+
+const STRINGS: Shape = Shape::Array(&Shape::String);
+
+const CONDITION: Shape = Shape::Table(&[
+    field(
+        "exists",
+        Shape::String,
+        "Path that must exist, resolved like `cwd`",
+    ),
+    field(
+        "types",
+        STRINGS,
+        "Exercise types (from meta.toml) the command applies to",
+    ),
+]);
+
+/// A study command: a shell string or a table with options.
+const COMMAND: Shape = Shape::OneOf(&[
+    Shape::String,
+    Shape::Table(&[
+        required(
+            "run",
+            Shape::String,
+            "The command to run; placeholders such as {exercise_dir} expand to quoted words, so don't quote them again",
+        ),
+        field(
+            "cwd",
+            Shape::String,
+            "Working directory, relative to the exercise unless it starts with a placeholder such as {course_dir}",
+        ),
+        field(
+            "env",
+            Shape::Map(&Shape::String),
+            "Environment variables to set",
+        ),
+        field(
+            "when",
+            CONDITION,
+            "Conditions that must all hold for the command to run",
+        ),
+        field(
+            "wait",
+            Shape::Boolean,
+            "Wait for the command to exit before starting the next one",
+        ),
+        field(
+            "detach",
+            Shape::Boolean,
+            "Start the command outside the session, so `study stop` leaves it running",
+        ),
+        field(
+            "shell",
+            Shape::Boolean,
+            "Run via `sh -c` (default); when false, `run` is split into words",
+        ),
+    ]),
+]);
+
+const STUDY: Shape = Shape::Table(&[
+    field(
+        "commands",
+        Shape::Array(&COMMAND),
+        "Commands started by `study`",
+    ),
+    field(
+        "inherit",
+        Shape::Boolean,
+        "Keep the commands inherited from the previous level (default true)",
+    ),
+]);
+
+const SUBMIT: Shape = Shape::Table(&[
+    field(
+        "include",
+        STRINGS,
+        "Globs of files to pack, relative to the exercise directory (default [\"**\"])",
+    ),
+    field(
+        "exclude",
+        STRINGS,
+        "Globs of files to leave out (default meta.toml and hidden files)",
+    ),
+    field(
+        "format",
+        Shape::Enum(&["zip", "tar.gz"]),
+        "Archive format (default zip)",
+    ),
+    field(
+        "output",
+        Shape::String,
+        "Archive path relative to the exercise directory, with placeholders such as {course.code}, {exercise.name} and {date}",
+    ),
+    field(
+        "build",
+        Shape::Array(&COMMAND),
+        "Commands run in the exercise directory before packing",
+    ),
+]);
+
+const EXERCISE_TYPE: Shape = Shape::Table(&[
+    required(
+        "template",
+        Shape::String,
+        "Template new exercises are created from",
+    ),
+    required(
+        "naming_scheme",
+        Shape::String,
+        "Directory names of new exercises, e.g. ex{:02} or week{week:02}-ex{n:02}",
+    ),
+    field(
+        "meta",
+        Shape::Map(&Shape::Any),
+        "Extra keys written to the [exercise] table of each new meta.toml",
+    ),
+    field(
+        "study",
+        STUDY,
+        "Study commands for exercises of this type, run after the course's",
+    ),
+    field(
+        "submit",
+        SUBMIT,
+        "How `study pack` packages exercises of this type",
+    ),
+]);
+
+const COURSE_INFO: Shape = Shape::Table(&[
+    required("code", Shape::String, "Course code, e.g. MS-C2286"),
+    field("name", Shape::String, "Human-readable course name"),
+    field(
+        "aliases",
+        STRINGS,
+        "Other names the course can be looked up by",
+    ),
+    field(
+        "term",
+        Shape::String,
+        "Term the course is taken in, e.g. 2025-autumn",
+    ),
+    field(
+        "archived",
+        Shape::Boolean,
+        "Hide the course unless --all is given",
+    ),
+]);
+
+const CODE_RULE: Shape = Shape::Table(&[
+    required("name", Shape::String, "Name of the rule"),
+    field(
+        "pattern",
+        Shape::String,
+        "Regex with named `faculty` and `code` captures",
+    ),
+    field(
+        "faculties",
+        STRINGS,
+        "Known faculty prefixes, optionally followed by a separator",
+    ),
+    field(
+        "builtin",
+        Shape::Enum(&["separator", "letters"]),
+        "Split at the first separator, or after the leading letters",
+    ),
+]);
+
+const VERSION: Field = field(
+    "version",
+    Shape::Integer,
+    "Format version; older files are migrated when loaded",
+);
+
+/// `course.toml`.
+pub const COURSE: Shape = Shape::Table(&[
+    VERSION,
+    required("course", COURSE_INFO, "The course"),
+    field(
+        "template_dir",
+        Shape::String,
+        "Directory of templates, relative to the course directory",
+    ),
+    field(
+        "exercise_types",
+        Shape::Map(&EXERCISE_TYPE),
+        "Kinds of exercises, by name",
+    ),
+    field(
+        "study",
+        STUDY,
+        "Study commands for the course, run after the global ones",
+    ),
+]);
+
+/// The global `config.toml`.
+pub const GLOBAL: Shape = Shape::Table(&[
+    VERSION,
+    required(
+        "courses_dir",
+        Shape::String,
+        "Directory containing the courses",
+    ),
+    required(
+        "default_template_dir",
+        Shape::String,
+        "Directory of templates shared by all courses",
+    ),
+    field(
+        "layout",
+        Shape::String,
+        "Where courses live under courses_dir, e.g. {year}/{term}/{faculty}/{code} (default {faculty}/{number})",
+    ),
+    field(
+        "code_rules",
+        Shape::Array(&CODE_RULE),
+        "Ordered rules for splitting course codes into faculty and number",
+    ),
+    field(
+        "discovery_depth",
+        Shape::Integer,
+        "Find courses by searching for course.toml up to this many levels deep instead of following layout",
+    ),
+    field(
+        "active_term",
+        Shape::String,
+        "Term of the courses currently being taken; courses of other terms are hidden",
+    ),
+    field(
+        "archive_dir",
+        Shape::String,
+        "Where `study archive` moves courses",
+    ),
+    field("study", STUDY, "Study commands inherited by every course"),
+]);
+
+/// An exercise's `meta.toml`.
+pub const META: Shape = Shape::Table(&[
+    required(
+        "course",
+        Shape::Table(&[
+            required("code", Shape::String, "Course code"),
+            field("name", Shape::String, "Course name"),
+        ]),
+        "The course the exercise belongs to",
+    ),
+    required(
+        "exercise",
+        Shape::OpenTable(&[
+            required("name", Shape::String, "Exercise directory name"),
+            required("type", Shape::String, "Exercise type from course.toml"),
+            field(
+                "status",
+                Shape::Enum(&["todo", "in-progress", "done", "submitted"]),
+                "Progress of the exercise (default todo)",
+            ),
+            field("due", Shape::Date, "Deadline"),
+            field("points", Shape::Number, "Points received"),
+            field("grade", Shape::String, "Grade received"),
+            field("tags", STRINGS, "Tags"),
+            field("title", Shape::String, "Title, matched by exercise lookups"),
+            field(
+                "calendar_uid",
+                Shape::String,
+                "UID of the calendar entry the deadline was imported from",
+            ),
+            field(
+                "submitted",
+                Shape::String,
+                "When `study pack` packed the exercise",
+            ),
+            field(
+                "submission",
+                Shape::String,
+                "Archive written by `study pack`",
+            ),
+        ]),
+        "The exercise; keys from the type's `meta` are kept here too",
+    ),
+]);
+
+/// A key that the schema does not allow.
+#[derive(Debug)]
+pub struct UnknownKey {
+    /// Dotted path such as `exercise_types.ex.naming-scheme`.
+    pub path: String,
+    /// Byte range of the key in the file.
+    pub span: Option<Range<usize>>,
+    /// The allowed key it most likely misspells.
+    pub suggestion: Option<&'static str>,
+}
+
+impl UnknownKey {
+    /// The key as a config error in the file at `path`, for rendering.
+    pub fn error(&self, path: &Path, contents: &str) -> Error {
+        let error = Error::config(
+            path,
+            contents,
+            format!("unknown key `{}`", self.path),
+            self.span.clone(),
+        );
+        match self.suggestion {
+            Some(suggestion) => error.with_hint(format!("did you mean `{}`?", suggestion)),
+            None => error,
+        }
+    }
+}
+
+/// The keys in the TOML `contents` that `shape` does not allow.
+pub fn unknown_keys(contents: &str, shape: &Shape) -> Result<Vec<UnknownKey>, TomlError> {
+    let document = ImDocument::parse(contents)?;
+    let mut keys = Vec::new();
+    check_table(document.as_table(), shape, "", &mut keys);
+    Ok(keys)
+}
+
+/// The table shape among `shape` and its alternatives.
+fn table_shape(shape: &Shape) -> Option<&Shape> {
+    match shape {
+        Shape::Table(_) | Shape::OpenTable(_) | Shape::Map(_) => Some(shape),
+        Shape::OneOf(shapes) => shapes.iter().find_map(table_shape),
+        _ => None,
+    }
+}
+
+fn check_item(item: &Item, shape: &Shape, path: &str, out: &mut Vec<UnknownKey>) {
+    // Values of the wrong type are reported by serde, not here.
+    if let (Some(table), Some(shape)) = (item.as_table_like(), table_shape(shape)) {
+        check_table(table, shape, path, out);
+        return;
+    }
+    let Shape::Array(element) = shape else {
+        return;
+    };
+    let Some(element) = table_shape(element) else {
+        return;
+    };
+    let index = |i| format!("{}[{}]", path, i);
+    if let Some(tables) = item.as_array_of_tables() {
+        for (i, table) in tables.iter().enumerate() {
+            check_table(table, element, &index(i), out);
+        }
+    } else if let Some(array) = item.as_array() {
+        for (i, value) in array.iter().enumerate() {
+            if let Some(table) = value.as_inline_table() {
+                check_table(table, element, &index(i), out);
+            }
+        }
+    }
+}
+
+fn check_table(table: &dyn TableLike, shape: &Shape, path: &str, out: &mut Vec<UnknownKey>) {
+    for (key, item) in table.iter() {
+        let child = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        match shape {
+            Shape::Table(fields) | Shape::OpenTable(fields) => {
+                match fields.iter().find(|field| field.name == key) {
+                    Some(field) => check_item(item, &field.shape, &child, out),
+                    None if matches!(shape, Shape::OpenTable(_)) => {}
+                    None => out.push(UnknownKey {
+                        path: child,
+                        span: table.key(key).and_then(|key| key.span()),
+                        suggestion: suggest(key, fields),
+                    }),
+                }
+            }
+            Shape::Map(shape) => check_item(item, shape, &child, out),
+            _ => {}
+        }
+    }
+}
+
+/// The field closest to `key`, if it is close enough to be a typo.
+fn suggest(key: &str, fields: &[Field]) -> Option<&'static str> {
+    fields
+        .iter()
+        .map(|field| (strsim::jaro_winkler(key, field.name), field.name))
+        .filter(|(score, _)| *score > 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, name)| name)
+}
+
+/// A JSON Schema (draft 7) for files of this `shape`, for TOML language
+/// servers that validate and complete against one.
+pub fn json_schema(shape: &Shape, title: &str) -> Value {
+    let mut schema = Map::new();
+    schema.insert(
+        "$schema".into(),
+        "http://json-schema.org/draft-07/schema#".into(),
+    );
+    schema.insert("title".into(), title.into());
+    if let Value::Object(body) = shape_schema(shape) {
+        schema.extend(body);
+    }
+    Value::Object(schema)
+}
+
+fn shape_schema(shape: &Shape) -> Value {
+    match shape {
+        Shape::Any => json!({}),
+        Shape::String => json!({ "type": "string" }),
+        Shape::Integer => json!({ "type": "integer", "minimum": 0 }),
+        Shape::Number => json!({ "type": "number" }),
+        Shape::Boolean => json!({ "type": "boolean" }),
+        // TOML language servers validate dates as strings.
+        Shape::Date => json!({ "type": "string", "pattern": "^\\d{4}-\\d{2}-\\d{2}" }),
+        Shape::Enum(values) => json!({ "type": "string", "enum": values }),
+        Shape::Table(fields) => table_schema(fields, false),
+        Shape::OpenTable(fields) => table_schema(fields, true),
+        Shape::Map(shape) => json!({
+            "type": "object",
+            "additionalProperties": shape_schema(shape),
+        }),
+        Shape::Array(shape) => json!({ "type": "array", "items": shape_schema(shape) }),
+        Shape::OneOf(shapes) => {
+            json!({ "anyOf": shapes.iter().map(shape_schema).collect::<Vec<_>>() })
+        }
+    }
+}
+
+fn table_schema(fields: &[Field], open: bool) -> Value {
+    let mut properties = Map::new();
+    for field in fields {
+        let mut schema = shape_schema(&field.shape);
+        if let Value::Object(schema) = &mut schema {
+            schema.insert("description".into(), field.description.into());
+        }
+        properties.insert(field.name.into(), schema);
+    }
+    let required: Vec<&str> = fields
+        .iter()
+        .filter(|field| field.required)
+        .map(|field| field.name)
+        .collect();
+
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    if !open {
+        schema["additionalProperties"] = json!(false);
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        ArchiveFormat, CommandSpec, Condition, CourseConfig, CourseInfo, ExerciseInfo,
+        ExerciseMeta, ExerciseStatus, ExerciseType, GlobalConfig, StudyCommand, StudyConfig,
+        SubmitConfig,
+    };
+    use crate::course_code::{Builtin, CodeRule, RuleKind};
+    use crate::layout::CourseLayout;
+    use regex::Regex;
+    use serde::Serialize;
+    use std::collections::HashMap;
+
+    /// Keys that `value` serializes to but `shape` doesn't describe.
+    fn undescribed(value: &impl Serialize, shape: &Shape) -> Vec<String> {
+        let contents = toml::to_string(value).unwrap();
+        unknown_keys(&contents, shape)
+            .unwrap()
+            .into_iter()
+            .map(|key| key.path)
+            .collect()
+    }
+
+    fn study() -> StudyConfig {
+        StudyConfig {
+            commands: vec![
+                StudyCommand::Shell("code .".into()),
+                StudyCommand::Spec(CommandSpec {
+                    run: "latexmk -pvc main.tex".into(),
+                    cwd: Some("{course_dir}".into()),
+                    env: HashMap::from([("TEXINPUTS".into(), ".:".into())]),
+                    when: Some(Condition {
+                        exists: Some("main.tex".into()),
+                        types: vec!["ex".into()],
+                    }),
+                    wait: true,
+                    detach: true,
+                    shell: false,
+                }),
+            ],
+            inherit: false,
+        }
+    }
+
+    fn course_info() -> CourseInfo {
+        CourseInfo {
+            code: "MS-A0011".into(),
+            name: Some("Linear Algebra".into()),
+            aliases: vec!["la".into()],
+            term: Some("2026-autumn".into()),
+            archived: true,
+        }
+    }
+
+    #[test]
+    fn global_schema_describes_every_field() {
+        // No `..Default::default()`, so that new fields have to be added here.
+        let config = GlobalConfig {
+            version: crate::config::CONFIG_VERSION,
+            courses_dir: "~/courses".into(),
+            default_template_dir: "~/.config/study/templates".into(),
+            layout: CourseLayout::default(),
+            discovery_depth: Some(3),
+            active_term: Some("2026-autumn".into()),
+            archive_dir: Some("~/archive".into()),
+            code_rules: vec![
+                CodeRule {
+                    name: "pattern".into(),
+                    kind: RuleKind::Pattern(Regex::new("(?<faculty>\\w+)(?<code>\\d+)").unwrap()),
+                },
+                CodeRule {
+                    name: "faculties".into(),
+                    kind: RuleKind::Faculties(vec!["MS".into()]),
+                },
+                CodeRule {
+                    name: "builtin".into(),
+                    kind: RuleKind::Builtin(Builtin::Letters),
+                },
+            ],
+            study: study(),
+        };
+        assert_eq!(undescribed(&config, &GLOBAL), Vec::<String>::new());
+    }
+
+    #[test]
+    fn course_schema_describes_every_field() {
+        let exercise_type = ExerciseType {
+            template: "latex".into(),
+            naming_scheme: "week{week:02}-ex{n:02}".parse().unwrap(),
+            meta: HashMap::from([("points_max".into(), toml::Value::Integer(10))]),
+            study: study(),
+            submit: Some(SubmitConfig {
+                include: vec!["*.pdf".into()],
+                exclude: vec!["*.aux".into()],
+                format: ArchiveFormat::TarGz,
+                output: "{course.code}-{exercise.name}".into(),
+                build: study().commands,
+            }),
+        };
+        let config = CourseConfig {
+            version: crate::config::CONFIG_VERSION,
+            course: course_info(),
+            template_dir: Some(".templates".into()),
+            exercise_types: HashMap::from([("ex".into(), exercise_type)]),
+            study: study(),
+        };
+        assert_eq!(undescribed(&config, &COURSE), Vec::<String>::new());
+    }
+
+    #[test]
+    fn meta_schema_describes_every_field() {
+        // Exercises only record the course's identity, not its settings.
+        let meta = ExerciseMeta {
+            course: CourseInfo {
+                code: "MS-A0011".into(),
+                name: Some("Linear Algebra".into()),
+                aliases: Vec::new(),
+                term: None,
+                archived: false,
+            },
+            exercise: ExerciseInfo {
+                name: "ex01".into(),
+                exercise_type: "ex".into(),
+                status: ExerciseStatus::default(),
+                due: Some("2026-11-01".parse().unwrap()),
+                points: Some(8.5),
+                grade: Some("5".into()),
+                tags: vec!["hard".into()],
+                extra: HashMap::new(),
+            },
+        };
+        assert_eq!(undescribed(&meta, &META), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unknown_keys_suggest_a_close_match() {
+        let keys = unknown_keys("[course]\ncode = \"X\"\nalias = [\"x\"]\n", &COURSE).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].path, "course.alias");
+        assert_eq!(keys[0].suggestion, Some("aliases"));
+    }
+}
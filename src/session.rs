@@ -0,0 +1,76 @@
+//! Process groups started by study sessions, saved so that `study stop`
+//! can signal them later.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Send `signal` to the process group led by `pid`. Returns false if the
+/// group no longer exists.
+fn signal_group(pid: u32, signal: libc::c_int) -> bool {
+    let Some(pid) = libc::pid_t::try_from(pid).ok().filter(|&pid| pid > 0) else {
+        return false;
+    };
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    unsafe { libc::kill(-pid, signal) == 0 }
+}
+
+/// A process group saved for a later `study stop`, identified by its leader's
+/// PID and start time so that a reused PID is not mistaken for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SavedGroup")]
+pub struct ProcessGroup {
+    pub pid: u32,
+    /// Start time of the leader in clock ticks since boot, where `/proc` has it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<u64>,
+}
+
+/// Older versions saved bare PIDs.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedGroup {
+    Pid(u32),
+    Group {
+        pid: u32,
+        #[serde(default)]
+        start: Option<u64>,
+    },
+}
+
+impl From<SavedGroup> for ProcessGroup {
+    fn from(saved: SavedGroup) -> Self {
+        match saved {
+            SavedGroup::Pid(pid) => Self { pid, start: None },
+            SavedGroup::Group { pid, start } => Self { pid, start },
+        }
+    }
+}
+
+impl ProcessGroup {
+    /// The group led by the running process `pid`.
+    pub fn new(pid: u32) -> Self {
+        Self {
+            pid,
+            start: process_start(pid),
+        }
+    }
+
+    /// Send `signal` to the group, unless its leader has exited and the PID
+    /// now belongs to another process. Returns false if the group is gone.
+    /// Groups without a start time are signalled as before.
+    pub fn signal(&self, signal: libc::c_int) -> bool {
+        if self.start.is_some() && process_start(self.pid) != self.start {
+            return false;
+        }
+        signal_group(self.pid, signal)
+    }
+}
+
+/// Start time of process `pid` in clock ticks since boot, from field 22 of
+/// `/proc/<pid>/stat`.
+fn process_start(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name in field 2 may contain spaces and parentheses.
+    let fields = &stat[stat.rfind(')')? + 1..];
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
@@ -0,0 +1,509 @@
+use crate::config::{CourseConfig, GlobalConfig, expand_tilde};
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Variables available to `{{name}}` placeholders in template files.
+pub type TemplateVars = HashMap<String, String>;
+
+/// Render `{{name}}` placeholders in `input` using `vars`.
+///
+/// Whitespace inside the braces is ignored, so `{{ course.code }}` works too.
+/// Extra braces around a placeholder are kept, so LaTeX's
+/// `\section{{{exercise.name}}}` renders as `\section{...}`. Unknown variables
+/// are left as-is. A backslash before the opening braces (`\{{`) produces a
+/// literal `{{` without substitution.
+pub fn render(input: &str, vars: &TemplateVars) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(mut pos) = rest.find("{{") {
+        if rest[..pos].ends_with('\\') {
+            out.push_str(&rest[..pos - 1]);
+            out.push_str("{{");
+            rest = &rest[pos + 2..];
+            continue;
+        }
+        // The placeholder opens at the last pair of a run of braces.
+        while rest[pos + 2..].starts_with('{') {
+            pos += 1;
+        }
+
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 2..];
+        match after.find("}}") {
+            Some(end) => match vars.get(after[..end].trim()) {
+                Some(value) => {
+                    out.push_str(value);
+                    rest = &after[end + 2..];
+                }
+                None => {
+                    out.push_str(&rest[pos..pos + 2 + end + 2]);
+                    rest = &after[end + 2..];
+                }
+            },
+            None => {
+                out.push_str(&rest[pos..]);
+                rest = "";
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Expand single-brace `{name}` placeholders, as used in study commands.
+///
+/// Only names present in `vars` are replaced, so shell syntax such as
+/// `${HOME}` or `awk '{print}'` passes through unchanged.
+pub fn expand(input: &str, vars: &TemplateVars) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('{') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        match after.find('}').and_then(|end| Some((end, vars.get(&after[..end])?))) {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Insert `value` under `prefix`, flattening nested tables into dotted keys.
+pub fn insert_flattened(vars: &mut TemplateVars, prefix: &str, value: &toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                insert_flattened(vars, &format!("{}.{}", prefix, key), value);
+            }
+        }
+        other => {
+            vars.insert(prefix.to_string(), value_to_string(other));
+        }
+    }
+}
+
+/// Copy a template directory to `dst`, rendering file contents and names.
+///
+/// Files that are not valid UTF-8 or contain NUL bytes are treated as binary
+/// and copied byte-for-byte. The template's own `template.toml` is skipped.
+pub fn copy_template(src: &Path, dst: &Path, vars: &TemplateVars) -> io::Result<()> {
+    copy_dir(src, dst, vars, true)
+}
+
+fn copy_dir(src: &Path, dst: &Path, vars: &TemplateVars, root: bool) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    fs::read_dir(src)?.try_for_each(|entry| {
+        let entry = entry?;
+        if root && entry.file_name() == MANIFEST_FILENAME {
+            return Ok(());
+        }
+        let name = render(&entry.file_name().to_string_lossy(), vars);
+        let dest = dst.join(name);
+        match entry.file_type()? {
+            ft if ft.is_dir() => copy_dir(&entry.path(), &dest, vars, false),
+            _ => copy_file(&entry.path(), &dest, vars),
+        }
+    })
+}
+
+fn copy_file(src: &Path, dst: &Path, vars: &TemplateVars) -> io::Result<()> {
+    let bytes = fs::read(src)?;
+    match String::from_utf8(bytes) {
+        Ok(text) if !text.contains('\0') => {
+            fs::write(dst, render(&text, vars))?;
+            fs::set_permissions(dst, fs::metadata(src)?.permissions())
+        }
+        _ => fs::copy(src, dst).map(|_| ()),
+    }
+}
+
+/// Convert a TOML value to the text inserted into a template.
+///
+/// Strings are inserted without quotes; everything else uses its TOML form.
+pub fn value_to_string(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+const MANIFEST_FILENAME: &str = "template.toml";
+const COURSE_TEMPLATES_DIRNAME: &str = ".templates";
+
+/// `(path, contents)` pairs of a built-in template.
+type BuiltinFiles = &'static [(&'static str, &'static str)];
+
+/// Built-in templates: name, description and files.
+const BUILTIN_TEMPLATES: &[(&str, &str, BuiltinFiles)] = &[
+    ("empty", "An empty exercise directory", &[]),
+    (
+        "latex",
+        "A LaTeX article with the course and exercise in the title",
+        &[(
+            "main.tex",
+            "\\documentclass[a4paper]{article}\n\
+             \\usepackage{amsmath, amssymb}\n\
+             \n\
+             \\title{{{course.code}} {{course.name}} -- {{exercise.name}}}\n\
+             \\date{{{date}}}\n\
+             \n\
+             \\begin{document}\n\
+             \\maketitle\n\
+             \n\
+             \\end{document}\n",
+        )],
+    ),
+    (
+        "python",
+        "A Python script with a header docstring",
+        &[(
+            "main.py",
+            "\"\"\"{{course.code}} {{course.name}}: {{exercise.name}} ({{date}}).\"\"\"\n\
+             \n\
+             \n\
+             def main():\n\
+             \x20   pass\n\
+             \n\
+             \n\
+             if __name__ == \"__main__\":\n\
+             \x20   main()\n",
+        )],
+    ),
+];
+
+/// Optional `template.toml` inside a template directory.
+#[derive(Debug, Default, Deserialize)]
+pub struct TemplateManifest {
+    /// Template whose files are copied first and then overlaid by this one.
+    #[serde(default)]
+    pub extends: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+pub enum TemplateSource {
+    Dir(PathBuf),
+    Builtin(BuiltinFiles),
+}
+
+pub struct Template {
+    pub name: String,
+    pub source: TemplateSource,
+    pub manifest: TemplateManifest,
+    /// Index into the registry's search path; built-ins come after all directories.
+    location: usize,
+}
+
+impl Template {
+    /// Human-readable origin: the template directory or "built-in".
+    pub fn origin(&self) -> String {
+        match &self.source {
+            TemplateSource::Dir(path) => path.display().to_string(),
+            TemplateSource::Builtin(_) => "built-in".to_string(),
+        }
+    }
+
+    /// Relative paths of the files this template provides (not including inherited ones).
+    pub fn files(&self) -> Vec<PathBuf> {
+        match &self.source {
+            TemplateSource::Dir(dir) => {
+                let mut files = Vec::new();
+                collect_files(dir, Path::new(""), &mut files);
+                files.retain(|f| f != Path::new(MANIFEST_FILENAME));
+                files.sort();
+                files
+            }
+            TemplateSource::Builtin(files) => files.iter().map(|(p, _)| PathBuf::from(p)).collect(),
+        }
+    }
+}
+
+/// The template search path for a course.
+///
+/// Templates are looked up in order in `<course>/.templates`, the course's
+/// `template_dir` (relative to the course directory), the global
+/// `default_template_dir`, and finally the built-in templates.
+pub struct TemplateRegistry {
+    dirs: Vec<PathBuf>,
+}
+
+impl TemplateRegistry {
+    pub fn new(global: &GlobalConfig, course: Option<(&Path, &CourseConfig)>) -> Self {
+        let mut dirs = Vec::new();
+        if let Some((course_dir, config)) = course {
+            dirs.push(course_dir.join(COURSE_TEMPLATES_DIRNAME));
+            if let Some(template_dir) = &config.template_dir {
+                dirs.push(course_dir.join(expand_tilde(template_dir)));
+            }
+        }
+        dirs.push(expand_tilde(&global.default_template_dir));
+        dirs.dedup();
+        Self { dirs }
+    }
+
+    /// Directories searched for templates, highest priority first.
+    pub fn search_path(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// The template `name`, or `None` if there is none. Fails if its
+    /// `template.toml` cannot be read.
+    pub fn find(&self, name: &str) -> Result<Option<Template>> {
+        self.find_from(name, 0)
+    }
+
+    fn find_from(&self, name: &str, start: usize) -> Result<Option<Template>> {
+        let from_dir = self
+            .dirs
+            .iter()
+            .enumerate()
+            .skip(start)
+            .map(|(i, dir)| (i, dir.join(name)))
+            .find(|(_, path)| path.is_dir());
+        if let Some((location, path)) = from_dir {
+            return Ok(Some(Template {
+                name: name.to_string(),
+                manifest: load_manifest(&path)?,
+                source: TemplateSource::Dir(path),
+                location,
+            }));
+        }
+
+        Ok(BUILTIN_TEMPLATES
+            .iter()
+            .find(|(builtin, _, _)| *builtin == name)
+            .map(|(_, description, files)| Template {
+                name: name.to_string(),
+                source: TemplateSource::Builtin(files),
+                manifest: TemplateManifest {
+                    extends: None,
+                    description: Some(description.to_string()),
+                },
+                location: self.dirs.len(),
+            }))
+    }
+
+    /// Resolve `name` and everything it extends, base template first.
+    ///
+    /// A template that extends its own name continues the search further down
+    /// the search path, so a course can overlay a global template of the same name.
+    pub fn chain(&self, name: &str) -> Result<Vec<Template>> {
+        let mut chain = vec![self.find(name)?.ok_or_else(|| self.not_found(name))?];
+
+        while let Some(parent) = chain.last().and_then(|t| t.manifest.extends.clone()) {
+            let child = chain.last().map_or(0, |t| t.location);
+            let start = if chain.last().is_some_and(|t| t.name == parent) {
+                child + 1
+            } else {
+                0
+            };
+            let template = self
+                .find_from(&parent, start)?
+                .ok_or_else(|| self.not_found(&parent))?;
+            if chain
+                .iter()
+                .any(|t| t.name == template.name && t.location == template.location)
+            {
+                return Err(Error::TemplateCycle {
+                    name: name.to_string(),
+                    parent,
+                });
+            }
+            chain.push(template);
+        }
+
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// All templates visible from this registry. Templates shadowed by a
+    /// higher-priority one of the same name are omitted.
+    pub fn list(&self) -> Result<Vec<Template>> {
+        self.names()
+            .iter()
+            .filter_map(|name| self.find(name).transpose())
+            .collect()
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .dirs
+            .iter()
+            .flat_map(|dir| fs::read_dir(dir).into_iter().flatten())
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|name| !name.starts_with('.'))
+            .chain(BUILTIN_TEMPLATES.iter().map(|(name, _, _)| name.to_string()))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Create `dst` from template `name`, applying inherited templates first.
+    pub fn instantiate(&self, name: &str, dst: &Path, vars: &TemplateVars) -> Result<()> {
+        let chain = self.chain(name)?;
+        self.copy_chain(&chain, dst, vars)
+            .map_err(|e| Error::io(dst, e))
+    }
+
+    fn copy_chain(&self, chain: &[Template], dst: &Path, vars: &TemplateVars) -> io::Result<()> {
+        fs::create_dir_all(dst)?;
+        for template in chain {
+            match &template.source {
+                TemplateSource::Dir(src) => copy_template(src, dst, vars)?,
+                TemplateSource::Builtin(files) => {
+                    for (path, contents) in files.iter() {
+                        let dest = dst.join(render(path, vars));
+                        if let Some(parent) = dest.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        fs::write(dest, render(contents, vars))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn not_found(&self, name: &str) -> Error {
+        Error::TemplateMissing {
+            name: name.to_string(),
+            searched: self.dirs.clone(),
+            available: self.names(),
+        }
+    }
+}
+
+/// Read the `template.toml` in `template_dir`; a template without one has
+/// the default manifest.
+fn load_manifest(template_dir: &Path) -> Result<TemplateManifest> {
+    let path = template_dir.join(MANIFEST_FILENAME);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(TemplateManifest::default()),
+        Err(e) => return Err(Error::io(path, e)),
+    };
+    toml::from_str(&contents).map_err(|e| Error::config(&path, &contents, e.message(), e.span()))
+}
+
+fn collect_files(dir: &Path, relative: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().filter_map(|e| e.ok()) {
+        let relative = relative.join(entry.file_name());
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            collect_files(&entry.path(), &relative, files);
+        } else {
+            files.push(relative);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> TemplateVars {
+        TemplateVars::from([
+            ("course.code".to_string(), "MS-C2286".to_string()),
+            ("exercise.name".to_string(), "ex01".to_string()),
+        ])
+    }
+
+    /// A fresh directory under the system temp dir for one test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("study-template-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn renders_known_variables() {
+        assert_eq!(
+            render("{{course.code}}: {{ exercise.name }}", &vars()),
+            "MS-C2286: ex01"
+        );
+        assert_eq!(
+            render("{{nope}} and {{course.code", &vars()),
+            "{{nope}} and {{course.code"
+        );
+    }
+
+    #[test]
+    fn extra_braces_wrap_the_value() {
+        assert_eq!(
+            render("\\section{{{exercise.name}}}", &vars()),
+            "\\section{ex01}"
+        );
+        assert_eq!(render("{{{{course.code}}}}", &vars()), "{{MS-C2286}}");
+        assert_eq!(render("\\title{{{nope}}}", &vars()), "\\title{{{nope}}}");
+    }
+
+    #[test]
+    fn backslash_escapes_the_opening_braces() {
+        assert_eq!(render("\\{{course.code}}", &vars()), "{{course.code}}");
+        assert_eq!(
+            render("\\{{{exercise.name}}}", &vars()),
+            "{{{exercise.name}}}"
+        );
+    }
+
+    #[test]
+    fn builtin_latex_title_has_no_padding() {
+        let (_, _, files) = BUILTIN_TEMPLATES
+            .iter()
+            .find(|(name, _, _)| *name == "latex")
+            .unwrap();
+        let mut vars = vars();
+        vars.insert("course.name".into(), "Linear Algebra".into());
+        vars.insert("date".into(), "2026-10-17".into());
+        let text = render(files[0].1, &vars);
+        assert!(
+            text.contains("\\title{MS-C2286 Linear Algebra -- ex01}\n"),
+            "{}",
+            text
+        );
+        assert!(text.contains("\\date{2026-10-17}\n"), "{}", text);
+    }
+
+    #[test]
+    fn copy_file_renders_text_and_passes_binary_through() {
+        let dir = scratch("copy");
+        let cases: [(&str, &[u8]); 3] = [
+            ("text", b"{{exercise.name}}\n"),
+            ("nul", b"{{exercise.name}}\0"),
+            ("binary", b"\xff\xfe{{exercise.name}}"),
+        ];
+        for (name, contents) in cases {
+            let src = dir.join(name);
+            fs::write(&src, contents).unwrap();
+            copy_file(&src, &dir.join(format!("{}.out", name)), &vars()).unwrap();
+        }
+        assert_eq!(fs::read(dir.join("text.out")).unwrap(), b"ex01\n");
+        assert_eq!(
+            fs::read(dir.join("nul.out")).unwrap(),
+            b"{{exercise.name}}\0"
+        );
+        assert_eq!(
+            fs::read(dir.join("binary.out")).unwrap(),
+            b"\xff\xfe{{exercise.name}}"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,449 @@
+use crate::config::{
+    self, ConfigFile, CourseConfig, CourseInfo, ExerciseMeta, ExerciseStatus, ExerciseType, GlobalConfig,
+    StudyState,
+};
+use crate::error::{Error, Result};
+use crate::history;
+use crate::template::{TemplateRegistry, TemplateVars, value_to_string};
+use chrono::NaiveDate;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// The courses and exercises managed by `study`, as configured by the global
+/// config in `~/.config/study/config.toml`.
+#[derive(Debug)]
+pub struct Workspace {
+    global: GlobalConfig,
+    unknown_keys: UnknownKeys,
+    warnings: RefCell<Vec<Error>>,
+    /// Config files whose unknown keys have been reported already.
+    checked: RefCell<HashSet<PathBuf>>,
+}
+
+/// What to do about keys in a config file that are not part of its format,
+/// usually misspellings that would otherwise be ignored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownKeys {
+    /// Collect them for `Workspace::take_warnings`.
+    #[default]
+    Warn,
+    /// Fail to load the file.
+    Deny,
+}
+
+/// A course directory and its `course.toml`.
+#[derive(Debug)]
+pub struct Course {
+    dir: PathBuf,
+    config: CourseConfig,
+}
+
+/// An exercise directory inside a course.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exercise {
+    dir: PathBuf,
+    name: String,
+}
+
+/// What to create with `Workspace::create_exercise`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NewExercise<'a> {
+    /// Exercise type from `course.toml`; may be omitted if there is only one.
+    pub exercise_type: Option<&'a str>,
+    /// Directory name, instead of the type's naming scheme.
+    pub name: Option<&'a str>,
+    pub due: Option<NaiveDate>,
+}
+
+/// An exercise made by `Workspace::create_exercise`, with what it was made from.
+#[derive(Debug, Clone)]
+pub struct CreatedExercise {
+    pub exercise: Exercise,
+    /// The exercise type's name in `course.toml`.
+    pub exercise_type: String,
+    pub template: String,
+}
+
+impl Workspace {
+    /// Load the global config, or use the defaults if there is none.
+    pub fn load() -> Result<Self> {
+        Self::load_with(UnknownKeys::default())
+    }
+
+    /// Like `load`, with `unknown_keys` deciding what happens to unknown keys
+    /// in the global config and in the courses opened later.
+    pub fn load_with(unknown_keys: UnknownKeys) -> Result<Self> {
+        let mut workspace = Self::new(GlobalConfig::default());
+        workspace.unknown_keys = unknown_keys;
+        if let Some(file) = config::load_global_config_file()? {
+            workspace.check_keys(&file)?;
+            workspace.global = file.config;
+        }
+        Ok(workspace)
+    }
+
+    pub fn new(global: GlobalConfig) -> Self {
+        Self {
+            global,
+            unknown_keys: UnknownKeys::default(),
+            warnings: RefCell::default(),
+            checked: RefCell::default(),
+        }
+    }
+
+    pub fn global(&self) -> &GlobalConfig {
+        &self.global
+    }
+
+    /// Active courses, or with `all` also archived courses and other terms.
+    /// Directories with an unreadable `course.toml` are skipped.
+    pub fn courses(&self, all: bool) -> Vec<Course> {
+        config::course_dirs(&self.global, all)
+            .filter_map(|dir| self.open_course(dir).ok())
+            .collect()
+    }
+
+    /// Find a course by code, name or alias; see `config::find_course_dir`.
+    pub fn find_course(&self, input: &str, all: bool) -> Result<Course> {
+        self.open_course(config::find_course_dir(&self.global, input, all)?)
+    }
+
+    /// The course containing `path`.
+    pub fn course_at(&self, path: &Path) -> Result<Course> {
+        let dir = config::find_course_root(path).ok_or_else(|| Error::NotInCourse(path.into()))?;
+        self.open_course(dir)
+    }
+
+    /// The course named `input`, or the one containing the current directory.
+    pub fn course_or_cwd(&self, input: Option<&str>) -> Result<Course> {
+        match input {
+            Some(input) => self.find_course(input, false),
+            None => {
+                let cwd = std::env::current_dir().map_err(|e| Error::io(".", e))?;
+                self.course_at(&cwd)
+            }
+        }
+    }
+
+    /// Read the course in `dir`, checking its `course.toml` for unknown keys.
+    pub fn open_course(&self, dir: impl Into<PathBuf>) -> Result<Course> {
+        let dir = dir.into();
+        let file = config::load_course_config_file(&dir)?;
+        self.check_keys(&file)?;
+        Ok(Course {
+            dir,
+            config: file.config,
+        })
+    }
+
+    /// Unknown keys found in the config files read so far, each file once.
+    pub fn take_warnings(&self) -> Vec<Error> {
+        self.warnings.take()
+    }
+
+    fn check_keys<T>(&self, file: &ConfigFile<T>) -> Result<()> {
+        let mut errors = file.unknown_key_errors();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        match self.unknown_keys {
+            UnknownKeys::Deny => Err(errors.swap_remove(0)),
+            UnknownKeys::Warn => {
+                if self.checked.borrow_mut().insert(file.path.clone()) {
+                    self.warnings.borrow_mut().extend(errors);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// The faculty of `course`, from its path under `courses_dir` or else its code.
+    pub fn faculty(&self, course: &Course) -> String {
+        config::course_faculty(&self.global, &course.dir, course.code())
+    }
+
+    /// Whether `course` is marked archived or lives under `archive_dir`.
+    pub fn is_archived(&self, course: &Course) -> bool {
+        config::is_archived(&self.global, &course.dir, &course.config.course)
+    }
+
+    /// Move `course` under `archive_dir` (keeping its path relative to
+    /// `courses_dir`), or mark it `archived = true` when no archive directory
+    /// is set. Returns where the course is now.
+    pub fn archive(&self, course: &Course) -> Result<PathBuf> {
+        let Some(archive_dir) = self.global.archive_dir.as_deref().map(config::expand_tilde)
+        else {
+            config::edit_course_config(&course.dir, |doc| {
+                doc["course"]["archived"] = toml_edit::value(true);
+            })?;
+            return Ok(course.dir.clone());
+        };
+
+        let relative = course
+            .dir
+            .strip_prefix(config::expand_tilde(&self.global.courses_dir))
+            .map_err(|_| {
+                let message = "Course is not inside courses_dir";
+                Error::io(&course.dir, io::Error::new(io::ErrorKind::InvalidInput, message))
+            })?;
+        let dest = archive_dir.join(relative);
+        if dest.exists() {
+            let message = format!("Archive destination already exists: {}", dest.display());
+            return Err(Error::io(
+                &course.dir,
+                io::Error::new(io::ErrorKind::AlreadyExists, message),
+            ));
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+        }
+        fs::rename(&course.dir, &dest).map_err(|e| Error::io(&course.dir, e))?;
+        Ok(dest)
+    }
+
+    pub fn state(&self) -> StudyState {
+        config::load_state()
+    }
+
+    pub fn save_state(&self, state: &StudyState) -> Result<()> {
+        config::save_state(state).map_err(|e| Error::io(config::state_path(), e))
+    }
+
+    /// Remember `course`, and `exercise` if given, as the most recently studied.
+    pub fn record_studied(&self, course: &Course, exercise: Option<&Exercise>) -> Result<()> {
+        let mut state = self.state();
+        state.record(course.code(), exercise.map(|e| e.name.clone()));
+        self.save_state(&state)
+    }
+
+    /// Send SIGTERM to the background commands of the last study session
+    /// and end it. Returns how many process groups were still running, or
+    /// `None` if there is no session to stop.
+    pub fn stop_session(&self) -> Result<Option<usize>> {
+        let mut state = self.state();
+        if state.session_pids.is_empty() {
+            return Ok(None);
+        }
+        let stopped = state
+            .session_pids
+            .iter()
+            .filter(|group| group.signal(libc::SIGTERM))
+            .count();
+
+        state.session_pids.clear();
+        if let Some(id) = state.session_id.take() {
+            history::finish(id).map_err(|e| Error::io(history::history_path(), e))?;
+        }
+        self.save_state(&state)?;
+        Ok(Some(stopped))
+    }
+
+    /// Create an exercise in `course` from its type's template.
+    pub fn create_exercise(&self, course: &Course, new: NewExercise) -> Result<CreatedExercise> {
+        let (type_name, ex_type) = course.exercise_type(new.exercise_type)?;
+
+        let name = match new.name {
+            Some(name) => name.to_string(),
+            None => ex_type
+                .naming_scheme
+                .next_name(&course.dir)
+                .map_err(|e| Error::io(&course.dir, e))?,
+        };
+
+        let dir = course.dir.join(&name);
+        if dir.exists() {
+            return Err(Error::ExerciseExists(dir));
+        }
+
+        let registry = TemplateRegistry::new(&self.global, Some((&course.dir, &course.config)));
+        let mut vars = template_vars(&course.config.course, &name, type_name, ex_type);
+        if let Some(due) = new.due {
+            vars.insert("exercise.due".into(), due.to_string());
+        }
+        registry.instantiate(&ex_type.template, &dir, &vars)?;
+
+        generate_meta(
+            &dir,
+            &course.config.course,
+            &name,
+            type_name,
+            new.due,
+            &ex_type.meta,
+        )
+        .map_err(|e| Error::io(dir.join("meta.toml"), e))?;
+
+        Ok(CreatedExercise {
+            exercise: Exercise { dir, name },
+            exercise_type: type_name.to_string(),
+            template: ex_type.template.clone(),
+        })
+    }
+}
+
+impl Course {
+    /// Read the course in `dir`.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let config = config::load_course_config(&dir)?;
+        Ok(Self { dir, config })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn config(&self) -> &CourseConfig {
+        &self.config
+    }
+
+    pub fn code(&self) -> &str {
+        &self.config.course.code
+    }
+
+    /// The course name, or its code if it has none.
+    pub fn display_name(&self) -> &str {
+        self.config.course.name.as_deref().unwrap_or(self.code())
+    }
+
+    pub fn exercises(&self) -> Vec<Exercise> {
+        config::exercise_dirs(&self.dir)
+            .filter_map(|dir| Exercise::at(dir).ok())
+            .collect()
+    }
+
+    /// Find an exercise by name, number, prefix or title; see `config::find_exercise_dir`.
+    pub fn find_exercise(&self, input: &str) -> Result<Exercise> {
+        Exercise::at(config::find_exercise_dir(&self.dir, &self.config, input)?)
+    }
+
+    /// The exercise type named `requested`, or the only one if none is requested.
+    pub fn exercise_type(&self, requested: Option<&str>) -> Result<(&str, &ExerciseType)> {
+        let types = &self.config.exercise_types;
+        if types.is_empty() {
+            return Err(Error::NoExerciseTypes {
+                course: self.code().to_string(),
+            });
+        }
+        let available = || {
+            let mut names: Vec<String> = types.keys().cloned().collect();
+            names.sort();
+            names
+        };
+        match requested {
+            Some(name) => types
+                .get_key_value(name)
+                .map(|(name, t)| (name.as_str(), t))
+                .ok_or_else(|| Error::UnknownExerciseType {
+                    name: name.to_string(),
+                    available: available(),
+                }),
+            None if types.len() == 1 => {
+                let (name, t) = types.iter().next().unwrap();
+                Ok((name.as_str(), t))
+            }
+            None => Err(Error::ExerciseTypeRequired {
+                available: available(),
+            }),
+        }
+    }
+}
+
+impl Exercise {
+    /// The exercise in `dir`.
+    pub fn at(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Not an exercise directory: {}", dir.display()),
+                )
+            })?;
+        Ok(Self { dir, name })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The exercise's `meta.toml`.
+    pub fn meta(&self) -> Result<ExerciseMeta> {
+        config::load_meta(&self.dir)
+    }
+
+    /// Edit `meta.toml` in place, preserving comments and formatting.
+    pub fn edit_meta(&self, edit: impl FnOnce(&mut toml_edit::DocumentMut)) -> Result<()> {
+        config::edit_meta(&self.dir, edit)
+    }
+}
+
+fn template_vars(
+    course: &CourseInfo,
+    exercise_name: &str,
+    type_name: &str,
+    ex_type: &ExerciseType,
+) -> TemplateVars {
+    let mut vars = TemplateVars::new();
+    vars.insert("course.code".into(), course.code.clone());
+    vars.insert(
+        "course.name".into(),
+        course.name.clone().unwrap_or_else(|| course.code.clone()),
+    );
+    vars.insert("exercise.name".into(), exercise_name.into());
+    vars.insert("exercise.type".into(), type_name.into());
+    if let Some(number) = ex_type.naming_scheme.number(exercise_name) {
+        vars.insert("exercise.number".into(), number.to_string());
+    }
+    vars.insert(
+        "date".into(),
+        chrono::Local::now().format("%Y-%m-%d").to_string(),
+    );
+    for (key, value) in &ex_type.meta {
+        vars.insert(format!("exercise.{}", key), value_to_string(value));
+    }
+    vars
+}
+
+fn generate_meta(
+    exercise_dir: &Path,
+    course: &CourseInfo,
+    exercise_name: &str,
+    type_name: &str,
+    due: Option<NaiveDate>,
+    custom_meta: &HashMap<String, toml::Value>,
+) -> io::Result<()> {
+    let mut meta = toml::map::Map::new();
+
+    let mut course_table = toml::map::Map::new();
+    course_table.insert("code".into(), toml::Value::String(course.code.clone()));
+    if let Some(name) = &course.name {
+        course_table.insert("name".into(), toml::Value::String(name.clone()));
+    }
+    meta.insert("course".into(), toml::Value::Table(course_table));
+
+    let mut exercise_table = toml::map::Map::new();
+    exercise_table.insert("name".into(), toml::Value::String(exercise_name.into()));
+    exercise_table.insert("type".into(), toml::Value::String(type_name.into()));
+    exercise_table.insert(
+        "status".into(),
+        toml::Value::String(ExerciseStatus::default().to_string()),
+    );
+    for (key, value) in custom_meta {
+        exercise_table.insert(key.clone(), value.clone());
+    }
+    if let Some(due) = due {
+        exercise_table.insert("due".into(), toml::Value::String(due.to_string()));
+    }
+    meta.insert("exercise".into(), toml::Value::Table(exercise_table));
+
+    let contents = toml::to_string_pretty(&meta).map_err(io::Error::other)?;
+    fs::write(exercise_dir.join("meta.toml"), contents)
+}